@@ -1,6 +1,6 @@
 use tree_sitter::Node;
 
-use crate::{message_helper::red, utility::get_source_code};
+use crate::{error::EnrichError, message_helper::red};
 
 // `c` => child
 // `cv` => child value
@@ -10,52 +10,44 @@ use crate::{message_helper::red, utility::get_source_code};
 // `by_k` => by kind
 #[allow(dead_code)]
 pub trait Accessor<'t> {
-    fn value(&self) -> String;
+    fn value(&self, source_code: &str) -> String;
 
-    fn first_c(&self) -> Node<'t>;
+    fn first_c(&self) -> Result<Node<'t>, EnrichError>;
 
     fn try_first_c(&self) -> Option<Node<'t>>;
     fn try_c_by_n(&self, kind: &str) -> Option<Node<'t>>;
     fn try_c_by_k(&self, kind: &str) -> Option<Node<'t>>;
     fn try_cs_by_k(&self, kind: &str) -> Vec<Node<'t>>;
 
-    fn c_by_n(&self, name: &str) -> Node<'t>;
-    fn c_by_k(&self, kind: &str) -> Node<'t>;
-    fn cvalue_by_n(&self, name: &str) -> String;
-    fn cvalue_by_k(&self, name: &str) -> String;
+    fn c_by_n(&self, name: &str) -> Result<Node<'t>, EnrichError>;
+    fn c_by_k(&self, kind: &str) -> Result<Node<'t>, EnrichError>;
+    fn cvalue_by_n(&self, name: &str, source_code: &str) -> Result<String, EnrichError>;
+    fn cvalue_by_k(&self, name: &str, source_code: &str) -> Result<String, EnrichError>;
 
     fn all_children_vec(&self) -> Vec<Node<'t>>;
     fn children_vec(&self) -> Vec<Node<'t>>;
-    fn cs_by_k(&self, kind: &str) -> Vec<Node<'t>>;
-    fn cs_by_n(&self, name: &str) -> Vec<Node<'t>>;
+    fn cs_by_k(&self, kind: &str) -> Result<Vec<Node<'t>>, EnrichError>;
+    fn cs_by_n(&self, name: &str) -> Result<Vec<Node<'t>>, EnrichError>;
 
-    fn next_named(&self) -> Node<'t>;
-
-    // private fn;
-    fn v<'a>(&self) -> &'a str;
-    fn cv_by_k(&self, name: &str) -> &str;
-    fn cv_by_n<'a>(&self, name: &str) -> &'a str;
+    fn next_named(&self) -> Result<Node<'t>, EnrichError>;
 }
 
 impl<'t> Accessor<'t> for Node<'t> {
-    fn next_named(&self) -> Node<'t> {
+    fn next_named(&self) -> Result<Node<'t>, EnrichError> {
         let mut sibling = self.next_named_sibling();
         while let Some(node) = sibling {
             if !node.is_extra() {
-                return node;
+                return Ok(node);
             }
             sibling = node.next_named_sibling();
         }
-        panic!("{}: next_named node missing.", red(self.kind()));
+        Err(EnrichError::new(self, "next named node missing"))
     }
 
-    fn v<'a>(&self) -> &'a str {
-        self.utf8_text(get_source_code().as_bytes())
+    fn value(&self, source_code: &str) -> String {
+        self.utf8_text(source_code.as_bytes())
             .unwrap_or_else(|_| panic!("{}: get AST source_code value failed.", red(self.kind())))
-    }
-
-    fn value(&self) -> String {
-        self.v().to_string()
+            .to_string()
     }
 
     fn children_vec(&self) -> Vec<Node<'t>> {
@@ -87,14 +79,9 @@ impl<'t> Accessor<'t> for Node<'t> {
         self.child_by_field_name(name)
     }
 
-    fn c_by_k(&self, kind: &str) -> Node<'t> {
-        self.try_c_by_k(kind).unwrap_or_else(|| {
-            panic!(
-                "## {}: missing mandatory kind child: {}\n ##Source_code: {}",
-                red(self.kind()),
-                red(kind),
-                self.value(),
-            )
+    fn c_by_k(&self, kind: &str) -> Result<Node<'t>, EnrichError> {
+        self.try_c_by_k(kind).ok_or_else(|| {
+            EnrichError::new(self, format!("missing mandatory kind child `{}`", kind))
         })
     }
 
@@ -109,80 +96,45 @@ impl<'t> Accessor<'t> for Node<'t> {
         None
     }
 
-    fn first_c(&self) -> Node<'t> {
-        let mut index = 0;
-        while let Some(node) = self.named_child(index) {
-            if !node.is_extra() {
-                return node;
-            }
-            index += 1;
-        }
-        panic!(
-            "{}: missing a mandatory child in first_c().",
-            red(self.kind())
-        );
-    }
-
-    fn cv_by_k(&self, name: &str) -> &str {
-        let child_node = self.c_by_k(name);
-        child_node.v()
-    }
-
-    fn cv_by_n<'a>(&self, name: &str) -> &'a str {
-        let node = self.child_by_field_name(name).unwrap_or_else(|| {
-            panic!(
-                "## {}: missing mandatory name child: {}\n ##Source_code: {}",
-                red(self.kind()),
-                red(name),
-                self.value(),
-            )
-        });
-        node.v()
+    fn first_c(&self) -> Result<Node<'t>, EnrichError> {
+        self.try_first_c()
+            .ok_or_else(|| EnrichError::new(self, "missing a mandatory child"))
     }
 
-    fn cvalue_by_n(&self, name: &str) -> String {
-        self.cv_by_n(name).to_string()
+    fn cvalue_by_n(&self, name: &str, source_code: &str) -> Result<String, EnrichError> {
+        Ok(self.c_by_n(name)?.value(source_code))
     }
 
-    fn cvalue_by_k(&self, name: &str) -> String {
-        self.cv_by_k(name).to_string()
+    fn cvalue_by_k(&self, name: &str, source_code: &str) -> Result<String, EnrichError> {
+        Ok(self.c_by_k(name)?.value(source_code))
     }
 
-    fn c_by_n(&self, name: &str) -> Node<'t> {
-        self.child_by_field_name(name).unwrap_or_else(|| {
-            panic!(
-                "## {}: missing mandatory name child: {}\n ##Source_code: {}",
-                red(self.kind()),
-                red(name),
-                self.value(),
-            )
+    fn c_by_n(&self, name: &str) -> Result<Node<'t>, EnrichError> {
+        self.child_by_field_name(name).ok_or_else(|| {
+            EnrichError::new(self, format!("missing mandatory name child `{}`", name))
         })
     }
 
-    fn cs_by_n(&self, name: &str) -> Vec<Node<'t>> {
+    fn cs_by_n(&self, name: &str) -> Result<Vec<Node<'t>>, EnrichError> {
         let mut cursor = self.walk();
         let children: Vec<Node<'t>> = self.children_by_field_name(name, &mut cursor).collect();
         if children.is_empty() {
-            panic!(
-                "## {}: missing mandatory name children: {}\n ##Source_code: {}",
-                red(self.kind()),
-                red(name),
-                self.value(),
-            );
+            return Err(EnrichError::new(
+                self,
+                format!("missing mandatory name children `{}`", name),
+            ));
         }
-        children
+        Ok(children)
     }
 
-    fn cs_by_k(&self, kind: &str) -> Vec<Node<'t>> {
+    fn cs_by_k(&self, kind: &str) -> Result<Vec<Node<'t>>, EnrichError> {
         let children = self.try_cs_by_k(kind);
         if children.is_empty() {
-            panic!(
-                "## {}: missing mandatory kind children: {}\n ##Source_code: {}",
-                red(self.kind()),
-                red(kind),
-                self.value(),
-            );
+            return Err(EnrichError::new(
+                self,
+                format!("missing mandatory kind children `{}`", kind),
+            ));
         }
-        children
+        Ok(children)
     }
 }
@@ -1,10 +1,40 @@
-use clap::{Arg as ClapArg, Command};
+use crate::range::SourceRange;
+use clap::{Arg as ClapArg, ArgGroup, Command};
 
 #[derive(Debug)]
 pub struct Args {
-    pub path: String,
+    pub paths: Vec<String>,
     pub config: Option<String>,
     pub write: bool,
+    pub check: bool,
+    pub diff: bool,
+    pub stdin: bool,
+    pub stdin_filepath: Option<String>,
+    pub jobs: Option<usize>,
+    pub range: Option<SourceRange>,
+    pub changed_since: Option<String>,
+    pub staged: bool,
+    pub patch: Option<String>,
+    pub verify_idempotent: bool,
+    pub safe: bool,
+    pub dump_doc: bool,
+    pub annotate_choices: bool,
+    pub inspect: Option<Inspect>,
+    pub report: Option<ReportFormat>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReportFormat {
+    // one JSON object per file and line
+    Json,
+}
+
+// `afmt inspect`: print what afmt sees in a file instead of formatting it
+#[derive(Debug)]
+pub struct Inspect {
+    pub file: String,
+    pub ast: bool,
+    pub comments: bool,
 }
 
 pub fn get_args() -> Args {
@@ -14,11 +44,42 @@ pub fn get_args() -> Args {
         .version(VERSION)
         .about(format!("Apex format tool (afmt): {}", VERSION))
         .arg_required_else_help(true)
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("inspect")
+                .about("Print the syntax tree or the comment attachment of a file, for bug reports")
+                .arg(
+                    ClapArg::new("file")
+                        .value_name("FILE")
+                        .help("Apex file to inspect")
+                        .required(true),
+                )
+                .arg(
+                    ClapArg::new("ast")
+                        .long("ast")
+                        .help("Print the tree-sitter tree with field names and line:column ranges")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    ClapArg::new("comments")
+                        .long("comments")
+                        .help("Print the pre, post and dangling comments of each node with their flags")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .group(
+                    ArgGroup::new("output")
+                        .args(["ast", "comments"])
+                        .required(true)
+                        .multiple(true),
+                ),
+        )
         .arg(
-            ClapArg::new("file")
-                .value_name("FILE")
-                .help("The relative path to the file to parse")
-                .required(true)
+            ClapArg::new("paths")
+                .value_name("PATHS")
+                .help("Files, directories or glob patterns to format")
+                .required_unless_present_any(["stdin", "changed-since", "staged", "patch"])
+                .num_args(1..)
                 .index(1),
         )
         .arg(
@@ -32,9 +93,141 @@ pub fn get_args() -> Args {
             ClapArg::new("write")
                 .short('w')
                 .long("write")
-                .help("Write the formatted result back to the file")
+                .help("Write the formatted result back to the files")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            ClapArg::new("check")
+                .long("check")
+                .help("List files that are not formatted and exit with code 2, without writing")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("write"),
+        )
+        .arg(
+            ClapArg::new("diff")
+                .long("diff")
+                .help("Print a unified diff of the changes instead of the formatted result")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("write"),
+        )
+        .arg(
+            ClapArg::new("stdin")
+                .long("stdin")
+                .help("Read the source from stdin and print only the formatted result to stdout")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["paths", "write", "check", "diff"]),
+        )
+        .arg(
+            ClapArg::new("stdin-filepath")
+                .long("stdin-filepath")
+                .value_name("PATH")
+                .help("Path of the file being piped in, used for config lookup and messages")
+                .requires("stdin"),
+        )
+        .arg(
+            ClapArg::new("jobs")
+                .short('j')
+                .long("jobs")
+                .value_name("N")
+                .help("Number of files to format in parallel [default: number of CPUs]")
+                .value_parser(clap::value_parser!(u32).range(1..)),
+        )
+        .arg(
+            ClapArg::new("range")
+                .long("range")
+                .value_name("START:END")
+                .help("Format only the statements and members covering these byte offsets")
+                .value_parser(parse_byte_range)
+                .conflicts_with("lines"),
+        )
+        .arg(
+            ClapArg::new("lines")
+                .long("lines")
+                .value_name("FIRST:LAST")
+                .help("Format only the statements and members covering these lines (1-based)")
+                .value_parser(parse_line_range),
+        )
+        .arg(
+            ClapArg::new("changed-since")
+                .long("changed-since")
+                .value_name("REV")
+                .help("Format only the lines changed since a git revision")
+                .conflicts_with_all(["stdin", "range", "lines", "patch"]),
+        )
+        .arg(
+            ClapArg::new("staged")
+                .long("staged")
+                .help("Format only the lines staged in git")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["stdin", "range", "lines", "patch"]),
+        )
+        .arg(
+            ClapArg::new("patch")
+                .long("patch")
+                .value_name("FILE")
+                .help("Format only the lines added by a unified diff, `-` reads it from stdin")
+                .conflicts_with_all(["stdin", "range", "lines"]),
+        )
+        .arg(
+            ClapArg::new("verify-idempotent")
+                .long("verify-idempotent")
+                .help("Format every result a second time and report files that change again")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all([
+                    "stdin",
+                    "range",
+                    "lines",
+                    "changed-since",
+                    "staged",
+                    "patch",
+                ]),
+        )
+        .arg(
+            ClapArg::new("safe")
+                .long("safe")
+                .help("Re-parse every result and skip files whose syntax tree changed [default with --write]")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            ClapArg::new("no-safe")
+                .long("no-safe")
+                .help("Write results without checking that the syntax tree is unchanged")
+                .action(clap::ArgAction::SetTrue)
+                .requires("write")
+                .conflicts_with("safe"),
+        )
+        .arg(
+            ClapArg::new("dump-doc")
+                .long("dump-doc")
+                .help("Print the Doc tree built for a single file or stdin instead of formatting")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all([
+                    "write",
+                    "check",
+                    "diff",
+                    "range",
+                    "lines",
+                    "changed-since",
+                    "staged",
+                    "patch",
+                    "verify-idempotent",
+                ]),
+        )
+        .arg(
+            ClapArg::new("annotate-choices")
+                .long("annotate-choices")
+                .help("With --dump-doc, mark each Choice with the branch that was printed")
+                .action(clap::ArgAction::SetTrue)
+                .requires("dump-doc"),
+        )
+        .arg(
+            ClapArg::new("report")
+                .long("report")
+                .value_name("FORMAT")
+                .help("Print one machine-readable record per file instead of text")
+                .value_parser(["json"])
+                .conflicts_with_all(["stdin", "dump-doc"]),
+        )
         .after_help(
             "EXAMPLES:\n\
              \n\
@@ -44,6 +237,45 @@ pub fn get_args() -> Args {
              # Format and write changes back to the file\n\
              afmt --write src/file.cls\n\
              \n\
+             # Format every Apex file under a directory\n\
+             afmt --write force-app/\n\
+             \n\
+             # Format files matching a glob pattern\n\
+             afmt --write \"force-app/**/*.trigger\"\n\
+             \n\
+             # CI: fail when any file is not formatted\n\
+             afmt --check force-app/\n\
+             \n\
+             # Review the changes as a unified diff\n\
+             afmt --diff force-app/ | less -R\n\
+             \n\
+             # Editor integration: format a buffer piped through stdin\n\
+             cat src/file.cls | afmt --stdin --stdin-filepath src/file.cls\n\
+             \n\
+             # Format only the statements touching lines 120 to 180\n\
+             afmt --write --lines 120:180 src/file.cls\n\
+             \n\
+             # Format only the lines changed since main\n\
+             afmt --write --changed-since main\n\
+             \n\
+             # Format only the lines added by a patch\n\
+             git diff -U0 main | afmt --write --patch -\n\
+             \n\
+             # Report files whose formatted output changes when formatted again\n\
+             afmt --verify-idempotent force-app/\n\
+             \n\
+             # Format on 4 threads\n\
+             afmt --jobs 4 --write force-app/\n\
+             \n\
+             # Debug a layout: print the Doc tree and which Choice branches were taken\n\
+             afmt --dump-doc --annotate-choices src/file.cls\n\
+             \n\
+             # Bug reports: print the syntax tree and where comments are attached\n\
+             afmt inspect --ast --comments src/file.cls\n\
+             \n\
+             # Dashboards: one JSON record per file, with status and changed lines\n\
+             afmt --check --report json force-app/ > report.jsonl\n\
+             \n\
              # Use a specific config file\n\
              afmt --config .afmt.toml ./file.cls\n\
             ",
@@ -51,11 +283,66 @@ pub fn get_args() -> Args {
         .get_matches();
 
     Args {
-        path: matches
-            .get_one::<String>("file")
-            .expect("File path is required")
-            .to_string(),
+        paths: matches
+            .get_many::<String>("paths")
+            .map(|paths| paths.cloned().collect())
+            .unwrap_or_default(),
         config: matches.get_one::<String>("config").map(|s| s.to_string()),
         write: matches.get_flag("write"),
+        check: matches.get_flag("check"),
+        diff: matches.get_flag("diff"),
+        stdin: matches.get_flag("stdin"),
+        stdin_filepath: matches
+            .get_one::<String>("stdin-filepath")
+            .map(|s| s.to_string()),
+        jobs: matches.get_one::<u32>("jobs").map(|n| *n as usize),
+        range: matches
+            .get_one::<SourceRange>("range")
+            .or_else(|| matches.get_one::<SourceRange>("lines"))
+            .cloned(),
+        changed_since: matches
+            .get_one::<String>("changed-since")
+            .map(|s| s.to_string()),
+        staged: matches.get_flag("staged"),
+        patch: matches.get_one::<String>("patch").map(|s| s.to_string()),
+        verify_idempotent: matches.get_flag("verify-idempotent"),
+        safe: matches.get_flag("safe")
+            || (matches.get_flag("write") && !matches.get_flag("no-safe")),
+        dump_doc: matches.get_flag("dump-doc"),
+        annotate_choices: matches.get_flag("annotate-choices"),
+        inspect: matches.subcommand_matches("inspect").map(|m| Inspect {
+            file: m.get_one::<String>("file").cloned().unwrap_or_default(),
+            ast: m.get_flag("ast"),
+            comments: m.get_flag("comments"),
+        }),
+        report: matches
+            .get_one::<String>("report")
+            .map(|_| ReportFormat::Json),
+    }
+}
+
+fn parse_pair(value: &str) -> Result<(usize, usize), String> {
+    let (start, end) = value
+        .split_once(':')
+        .ok_or_else(|| format!("expected START:END, got `{}`", value))?;
+    let start: usize = start
+        .parse()
+        .map_err(|_| format!("invalid start `{}`", start))?;
+    let end: usize = end.parse().map_err(|_| format!("invalid end `{}`", end))?;
+    if start > end {
+        return Err(format!("start {} is after end {}", start, end));
+    }
+    Ok((start, end))
+}
+
+fn parse_byte_range(value: &str) -> Result<SourceRange, String> {
+    let (start, end) = parse_pair(value)?;
+    Ok(SourceRange::Bytes(start..end))
+}
+
+fn parse_line_range(value: &str) -> Result<SourceRange, String> {
+    match parse_pair(value)? {
+        (0, _) => Err("lines start at 1".to_string()),
+        (first, last) => Ok(SourceRange::Lines(first, last)),
     }
 }
@@ -0,0 +1,10 @@
+use std::io;
+use std::process;
+
+fn main() {
+    let stdin = io::stdin();
+    if let Err(e) = afmt::lsp::run(stdin.lock(), io::stdout()) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -6,7 +6,8 @@ use crate::{
     data_model::DocBuild,
     doc::DocRef,
     doc_builder::DocBuilder,
-    utility::{is_bracket_composite_node, panic_unknown_node},
+    error::EnrichError,
+    utility::{is_bracket_composite_node, unknown_node},
 };
 
 pub type CommentMap = HashMap<usize, CommentBucket>;
@@ -31,6 +32,8 @@ pub struct CommentBucket {
     pub pre_comments: Vec<Comment>,
     pub post_comments: Vec<Comment>,
     pub dangling_comments: Vec<Comment>,
+    // byte range of a node covered by `afmt-ignore` or `afmt-off`, printed as in the source
+    pub ignored_source: Option<std::ops::Range<usize>>,
 }
 
 impl CommentBucket {
@@ -39,6 +42,7 @@ impl CommentBucket {
             pre_comments: Vec::new(),
             post_comments: Vec::new(),
             dangling_comments: Vec::new(),
+            ignored_source: None,
         }
     }
 }
@@ -56,12 +60,14 @@ pub struct Comment {
     pub comment_type: CommentType,
     pub metadata: CommentMetadata,
     pub is_printed: Cell<bool>,
+    // where the comment is in the source
+    pub range: Range,
 }
 
 impl Comment {
-    pub fn from_node(node: Node) -> Self {
+    pub fn from_node(node: Node, source_code: &str) -> Result<Self, EnrichError> {
         //let id = node.id();
-        let value = node.value().trim_end().to_string();
+        let value = node.value(source_code).trim_end().to_string();
         let (comment_type, metadata) = match node.kind() {
             "line_comment" => {
                 let metadata = CommentMetadata::from(&node, CommentType::Line);
@@ -71,16 +77,17 @@ impl Comment {
                 let metadata = CommentMetadata::from(&node, CommentType::Block);
                 (CommentType::Block, metadata)
             }
-            _ => panic_unknown_node(node, "Comment"),
+            _ => return Err(unknown_node(node, "Comment")),
         };
 
-        Self {
+        Ok(Self {
             //id,
             value,
             comment_type,
             metadata,
             is_printed: Cell::new(false),
-        }
+            range: node.range(),
+        })
     }
 
     pub fn has_leading_content(&self) -> bool {
@@ -107,6 +114,15 @@ impl Comment {
         self.metadata.has_prev_node
     }
 
+    // e.g. `// afmt-ignore` or `/* afmt-off */`
+    pub fn is_directive(&self, directive: &str) -> bool {
+        let text = match self.comment_type {
+            CommentType::Line => self.value.trim_start_matches('/'),
+            CommentType::Block => self.value.trim_start_matches("/*").trim_end_matches("*/"),
+        };
+        text.trim() == directive
+    }
+
     pub fn mark_as_printed(&self) {
         self.is_printed.set(true);
     }
@@ -203,6 +219,29 @@ impl CommentMetadata {
         }
     }
 
+    // names of the flags that are set, for `afmt inspect --comments`
+    pub fn flags(&self) -> Vec<&'static str> {
+        [
+            ("has_leading_content", self.has_leading_content),
+            ("has_trailing_content", self.has_trailing_content),
+            ("has_newline_above", self.has_newline_above),
+            ("has_newline_below", self.has_newline_below),
+            ("has_prev_node", self.has_prev_node),
+            (
+                "is_followed_by_bracket_composite_node",
+                self.is_followed_by_bracket_composite_node,
+            ),
+            (
+                "is_line_comment_and_need_newline",
+                self.is_line_comment_and_need_newline,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, set)| *set)
+        .map(|(name, _)| name)
+        .collect()
+    }
+
     fn is_line_comment_and_need_newline(node: &Node, comment_type: CommentType) -> bool {
         if comment_type != CommentType::Line {
             return false;
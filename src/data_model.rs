@@ -1,5 +1,11 @@
 use crate::{
-    accessor::Accessor, context::NodeInfo, doc::DocRef, doc_builder::{DocBuilder, Insertable}, enum_def::*, message_helper::red, utility::*
+    accessor::Accessor,
+    context::NodeInfo,
+    doc::DocRef,
+    doc_builder::{DocBuilder, Insertable},
+    enum_def::*,
+    error::EnrichError,
+    utility::*,
 };
 use std::fmt::Debug;
 use toml::Value;
@@ -22,24 +28,24 @@ pub struct Root {
 }
 
 impl Root {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "parser_output");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "parser_output")?;
 
         let members: Vec<_> = node
             .children_vec()
             .into_iter()
-            .map(|n| BodyMember::new(&n, RootMember::new(n)))
-            .collect();
+            .map(|n| Ok(BodyMember::new(&n, RootMember::new(n, source)?)))
+            .collect::<Result<_, _>>()?;
 
         let node_info = NodeInfo::from(&node);
 
-        Self { members, node_info }
+        Ok(Self { members, node_info })
     }
 }
 
 impl<'a> DocBuild<'a> for Root {
     fn build_inner(&self, b: &'a DocBuilder<'a>, result: &mut Vec<DocRef<'a>>) {
-        let bucket = get_comment_bucket(&self.node_info.id);
+        let bucket = b.comment_bucket(&self.node_info.id);
         if !bucket.dangling_comments.is_empty() {
             let docs: Vec<_> = bucket
                 .dangling_comments
@@ -67,20 +73,30 @@ pub struct ClassDeclaration {
 }
 
 impl ClassDeclaration {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "class_declaration");
-
-        Self {
-            modifiers: node.try_c_by_k("modifiers").map(|n| Modifiers::new(n)),
-            name: ValueNode::new(node.c_by_n("name")),
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "class_declaration")?;
+
+        Ok(Self {
+            modifiers: node
+                .try_c_by_k("modifiers")
+                .map(|n| Modifiers::new(n, source))
+                .transpose()?,
+            name: ValueNode::new(node.c_by_n("name")?, source)?,
             type_parameters: node
                 .try_c_by_k("type_parameters")
-                .map(|n| TypeParameters::new(n)),
-            superclass: node.try_c_by_k("superclass").map(|n| SuperClass::new(n)),
-            interface: node.try_c_by_k("interfaces").map(|n| Interface::new(n)),
-            body: ClassBody::new(node.c_by_n("body")),
+                .map(|n| TypeParameters::new(n, source))
+                .transpose()?,
+            superclass: node
+                .try_c_by_k("superclass")
+                .map(|n| SuperClass::new(n, source))
+                .transpose()?,
+            interface: node
+                .try_c_by_k("interfaces")
+                .map(|n| Interface::new(n, source))
+                .transpose()?,
+            body: ClassBody::new(node.c_by_n("body")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -135,17 +151,23 @@ pub struct MethodDeclaration {
 }
 
 impl MethodDeclaration {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "method_declaration");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "method_declaration")?;
 
-        Self {
-            modifiers: node.try_c_by_k("modifiers").map(|n| Modifiers::new(n)),
-            type_: UnannotatedType::new(node.c_by_n("type")),
-            name: ValueNode::new(node.c_by_n("name")),
-            formal_parameters: FormalParameters::new(node.c_by_n("parameters")),
-            body: node.try_c_by_n("body").map(|n| Block::new(n)),
+        Ok(Self {
+            modifiers: node
+                .try_c_by_k("modifiers")
+                .map(|n| Modifiers::new(n, source))
+                .transpose()?,
+            type_: UnannotatedType::new(node.c_by_n("type")?, source)?,
+            name: ValueNode::new(node.c_by_n("name")?, source)?,
+            formal_parameters: FormalParameters::new(node.c_by_n("parameters")?, source)?,
+            body: node
+                .try_c_by_n("body")
+                .map(|n| Block::new(n, source))
+                .transpose()?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -179,19 +201,19 @@ pub struct FormalParameters {
 }
 
 impl FormalParameters {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "formal_parameters");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "formal_parameters")?;
 
         let formal_parameters = node
             .try_cs_by_k("formal_parameter")
             .into_iter()
-            .map(FormalParameter::new)
-            .collect();
+            .map(|n| FormalParameter::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             formal_parameters,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -219,16 +241,22 @@ pub struct FormalParameter {
 }
 
 impl FormalParameter {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "formal_parameter");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "formal_parameter")?;
 
-        Self {
-            modifiers: node.try_c_by_k("modifiers").map(Modifiers::new),
-            type_: UnannotatedType::new(node.c_by_n("type")),
-            name: ValueNode::new(node.c_by_n("name")),
-            dimensions: node.try_c_by_k("dimensions").map(Dimensions::new),
+        Ok(Self {
+            modifiers: node
+                .try_c_by_k("modifiers")
+                .map(|n| Modifiers::new(n, source))
+                .transpose()?,
+            type_: UnannotatedType::new(node.c_by_n("type")?, source)?,
+            name: ValueNode::new(node.c_by_n("name")?, source)?,
+            dimensions: node
+                .try_c_by_k("dimensions")
+                .map(|n| Dimensions::new(n, source))
+                .transpose()?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -256,13 +284,13 @@ pub struct SuperClass {
 }
 
 impl SuperClass {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "superclass");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "superclass")?;
 
-        Self {
-            type_: Type::new(node.first_c()),
+        Ok(Self {
+            type_: Type::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -283,20 +311,23 @@ pub struct Modifiers {
 }
 
 impl Modifiers {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "modifiers");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "modifiers")?;
 
         let modifiers = node
             .try_cs_by_k("modifier")
             .into_iter()
-            .map(Modifier::new)
-            .collect();
-
-        Self {
-            annotation: node.try_c_by_k("annotation").map(Annotation::new),
+            .map(|n| Modifier::new(n, source))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            annotation: node
+                .try_c_by_k("annotation")
+                .map(|n| Annotation::new(n, source))
+                .transpose()?,
             modifiers,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -324,13 +355,13 @@ pub struct Modifier {
 }
 
 impl Modifier {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "modifier");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "modifier")?;
 
-        Self {
-            kind: ModifierKind::new(node.first_c()),
+        Ok(Self {
+            kind: ModifierKind::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -350,18 +381,19 @@ pub struct Annotation {
 }
 
 impl Annotation {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "annotation");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "annotation")?;
 
         let arguments = node
             .try_c_by_n("arguments")
-            .map(AnnotationArgumentList::new);
+            .map(|n| AnnotationArgumentList::new(n, source))
+            .transpose()?;
 
-        Self {
-            name: ValueNode::new(node.c_by_n("name")),
+        Ok(Self {
+            name: ValueNode::new(node.c_by_n("name")?, source)?,
             arguments,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -390,14 +422,14 @@ pub struct AnnotationKeyValue {
 }
 
 impl AnnotationKeyValue {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "annotation_key_value");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "annotation_key_value")?;
 
-        Self {
-            key: ValueNode::new(node.c_by_n("key")),
-            value: ValueNode::new(node.c_by_n("value")),
+        Ok(Self {
+            key: ValueNode::new(node.c_by_n("key")?, source)?,
+            value: ValueNode::new(node.c_by_n("value")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -418,34 +450,28 @@ pub struct ClassBody {
 }
 
 impl ClassBody {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "class_body");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "class_body")?;
 
         let class_members: Vec<_> = node
             .children_vec()
             .into_iter()
-            .map(|n| BodyMember::new(&n, ClassMember::new(n)))
-            .collect();
+            .map(|n| Ok(BodyMember::new(&n, ClassMember::new(n, source)?)))
+            .collect::<Result<_, _>>()?;
         let node_info = NodeInfo::from(&node);
 
-        Self {
+        Ok(Self {
             class_members,
             node_info,
-        }
+        })
     }
 }
 
 impl<'a> DocBuild<'a> for ClassBody {
     fn build_inner(&self, b: &'a DocBuilder<'a>, result: &mut Vec<DocRef<'a>>) {
-        let bucket = get_comment_bucket(&self.node_info.id);
-        handle_pre_comments(b, bucket, result);
-
-        if bucket.dangling_comments.is_empty() {
+        build_body_with_comments(b, &self.node_info.id, result, |b, result| {
             result.push(b.surround_body_members(&self.class_members, "{", "}"));
-            handle_post_comments(b, bucket, result);
-        } else {
-            handle_dangling_comments_in_bracket_surround(b, bucket, result);
-        }
+        });
     }
 }
 
@@ -459,28 +485,32 @@ pub struct FieldDeclaration {
 }
 
 impl FieldDeclaration {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "field_declaration");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "field_declaration")?;
 
-        let modifiers = node.try_c_by_k("modifiers").map(|n| Modifiers::new(n));
+        let modifiers = node
+            .try_c_by_k("modifiers")
+            .map(|n| Modifiers::new(n, source))
+            .transpose()?;
 
         let declarators = node
-            .cs_by_n("declarator")
+            .cs_by_n("declarator")?
             .into_iter()
-            .map(|n| VariableDeclarator::new(n))
-            .collect();
+            .map(|n| VariableDeclarator::new(n, source))
+            .collect::<Result<_, _>>()?;
 
         let accessor_list = node
             .try_c_by_k("accessor_list")
-            .map(|n| AccessorList::new(n));
+            .map(|n| AccessorList::new(n, source))
+            .transpose()?;
 
-        Self {
+        Ok(Self {
             modifiers,
-            type_: UnannotatedType::new(node.c_by_n("type")),
+            type_: UnannotatedType::new(node.c_by_n("type")?, source)?,
             declarators,
             accessor_list,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -522,19 +552,19 @@ pub struct ArrayInitializer {
 }
 
 impl ArrayInitializer {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "array_initializer");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "array_initializer")?;
 
         let initializers: Vec<_> = node
             .children_vec()
             .into_iter()
-            .map(|n| VariableInitializer::new(n))
-            .collect();
+            .map(|n| VariableInitializer::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             initializers,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -562,18 +592,18 @@ pub struct AssignmentExpression {
 }
 
 impl AssignmentExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "assignment_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "assignment_expression")?;
 
-        let right_child = node.c_by_n("right");
+        let right_child = node.c_by_n("right")?;
 
-        Self {
-            left: AssignmentLeft::new(node.c_by_n("left")),
-            op: ValueNode::new(node.c_by_n("operator")),
-            right: Expression::new(right_child),
+        Ok(Self {
+            left: AssignmentLeft::new(node.c_by_n("left")?, source)?,
+            op: ValueNode::new(node.c_by_n("operator")?, source)?,
+            right: Expression::new(right_child, source)?,
             is_right_child_a_query_node: is_query_expression(&right_child),
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -602,13 +632,13 @@ pub enum AssignmentLeft {
 }
 
 impl AssignmentLeft {
-    pub fn new(node: Node) -> Self {
-        match node.kind() {
-            "identifier" => Self::Identifier(ValueNode::new(node)),
-            "field_access" => Self::Field(FieldAccess::new(node)),
-            "array_access" => Self::Array(ArrayAccess::new(node)),
-            _ => panic_unknown_node(node, "AssignmentLeft"),
-        }
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match node.kind() {
+            "identifier" => Self::Identifier(ValueNode::new(node, source)?),
+            "field_access" => Self::Field(FieldAccess::new(node, source)?),
+            "array_access" => Self::Array(ArrayAccess::new(node, source)?),
+            _ => return Err(unknown_node(node, "AssignmentLeft")),
+        })
     }
 }
 
@@ -634,12 +664,12 @@ pub struct BoolType {
 }
 
 impl BoolType {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "boolean_type");
+    pub fn new(node: Node, _source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "boolean_type")?;
 
-        Self {
+        Ok(Self {
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -658,36 +688,29 @@ pub struct Block {
 }
 
 impl Block {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "block");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "block")?;
 
         let statements: Vec<BodyMember<Statement>> = node
             .children_vec()
             .into_iter()
-            .map(|n| BodyMember::new(&n, Statement::new(n)))
-            .collect();
+            .map(|n| Ok(BodyMember::new(&n, Statement::new(n, source)?)))
+            .collect::<Result<_, _>>()?;
         let node_info = NodeInfo::from(&node);
 
-        Self {
+        Ok(Self {
             statements,
             node_info,
-        }
+        })
     }
 }
 
 impl<'a> DocBuild<'a> for Block {
     fn build_inner(&self, b: &'a DocBuilder<'a>, result: &mut Vec<DocRef<'a>>) {
-        let bucket = get_comment_bucket(&self.node_info.id);
-        handle_pre_comments(b, bucket, result);
-
-        if bucket.dangling_comments.is_empty() {
+        build_body_with_comments(b, &self.node_info.id, result, |b, result| {
             let docs = b.surround_body_members(&self.statements, "{", "}");
             result.push(docs);
-        } else {
-            handle_dangling_comments_in_bracket_surround(b, bucket, result);
-            return;
-        }
-        handle_post_comments(b, bucket, result);
+        });
     }
 }
 
@@ -698,13 +721,13 @@ pub struct Interface {
 }
 
 impl Interface {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "interfaces");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "interfaces")?;
 
-        Self {
-            type_list: TypeList::new(node.c_by_k("type_list")),
+        Ok(Self {
+            type_list: TypeList::new(node.c_by_k("type_list")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -725,19 +748,19 @@ pub struct TypeList {
 }
 
 impl TypeList {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "type_list");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "type_list")?;
 
         let types = node
             .children_vec()
             .into_iter()
-            .map(|n| Type::new(n))
-            .collect();
+            .map(|n| Type::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             types,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -765,12 +788,14 @@ pub enum ObjectExpression {
 }
 
 impl ObjectExpression {
-    pub fn new(node: Node) -> Self {
-        //TODO: handle incoming comment node
-        match node.kind() {
-            "super" => Self::Super(Super::new(node)),
-            _ => Self::Primary(Box::new(PrimaryExpression::new(node))),
-        }
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(
+            //TODO: handle incoming comment node
+            match node.kind() {
+                "super" => Self::Super(Super::new(node, source)?),
+                _ => Self::Primary(Box::new(PrimaryExpression::new(node, source)?)),
+            },
+        )
     }
 }
 
@@ -863,24 +888,25 @@ pub struct MethodInvocation {
 }
 
 impl MethodInvocation {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "method_invocation");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "method_invocation")?;
 
-        let name = ValueNode::new(node.c_by_n("name"));
-        let arguments = ArgumentList::new(node.c_by_n("arguments"));
+        let name = ValueNode::new(node.c_by_n("name")?, source)?;
+        let arguments = ArgumentList::new(node.c_by_n("arguments")?, source)?;
 
         let kind = if let Some(obj) = node.try_c_by_n("object") {
-            let object = ObjectExpression::new(obj);
-            let next_named = obj.next_named();
+            let object = ObjectExpression::new(obj, source)?;
+            let next_named = obj.next_named()?;
             let property_navigation = if next_named.kind() == "safe_navigation_operator" {
-                PropertyNavigation::Safe(SafeNavigationOperator::new(next_named))
+                PropertyNavigation::Safe(SafeNavigationOperator::new(next_named, source)?)
             } else {
                 PropertyNavigation::Dot
             };
 
             let type_arguments = node
                 .try_c_by_k("type_arguments")
-                .map(|n| TypeArguments::new(n));
+                .map(|n| TypeArguments::new(n, source))
+                .transpose()?;
             let context = build_chaining_context(&node);
 
             MethodInvocationKind::Complex {
@@ -895,10 +921,10 @@ impl MethodInvocation {
             MethodInvocationKind::Simple { name, arguments }
         };
 
-        Self {
+        Ok(Self {
             kind,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -936,17 +962,17 @@ pub struct TypeArguments {
 }
 
 impl TypeArguments {
-    pub fn new(node: Node) -> Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
         let types = node
             .children_vec()
             .into_iter()
-            .map(|n| Type::new(n))
-            .collect();
+            .map(|n| Type::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             types,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -970,17 +996,17 @@ pub struct ArgumentList {
 }
 
 impl ArgumentList {
-    pub fn new(node: Node) -> Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
         let expressions = node
             .children_vec()
             .into_iter()
-            .map(|n| Expression::new(n))
-            .collect();
+            .map(|n| Expression::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             expressions,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -990,6 +1016,12 @@ impl<'a> DocBuild<'a> for ArgumentList {
             let docs = b.to_docs(&self.expressions);
 
             let sep = Insertable::new(None, Some(","), Some(b.softline()));
+            if b.visual_indent() {
+                // arguments after the first one line up with it
+                let args = b.align(0, b.group(b.intersperse(&docs, sep)));
+                return result.push(b.concat(vec![b.txt("("), args, b.txt(")")]));
+            }
+
             let open = Insertable::new(None, Some("("), Some(b.maybeline()));
             let close = Insertable::new(Some(b.maybeline()), Some(")"), None);
             let doc = b.group_surround(&docs, sep, open, close);
@@ -1004,12 +1036,12 @@ pub struct Super {
 }
 
 impl Super {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "super");
+    pub fn new(node: Node, _source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "super")?;
 
-        Self {
+        Ok(Self {
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1027,12 +1059,12 @@ pub struct This {
 }
 
 impl This {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "this");
+    pub fn new(node: Node, _source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "this")?;
 
-        Self {
+        Ok(Self {
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1061,34 +1093,33 @@ pub struct BinaryExpression {
 }
 
 impl BinaryExpression {
-    fn build_context(node: &Node) -> BinaryExpressionContext {
-        let op = node.c_by_n("operator").kind();
-        let precedence = get_precedence(op);
+    fn build_context(node: &Node) -> Result<BinaryExpressionContext, EnrichError> {
+        let precedence = get_precedence(&node.c_by_n("operator")?)?;
         let parent = node
             .parent()
             .expect("BinaryExpression node should always have a parent");
 
         let is_a_chaining_inner_node = is_binary_exp(&parent);
-        let has_parent_same_precedence = is_binary_exp(&parent)
-            && precedence == get_precedence(parent.c_by_n("operator").kind());
+        let has_parent_same_precedence =
+            is_binary_exp(&parent) && precedence == get_precedence(&parent.c_by_n("operator")?)?;
 
-        BinaryExpressionContext {
+        Ok(BinaryExpressionContext {
             has_parent_same_precedence,
             is_a_chaining_inner_node,
             is_parent_return_statement: parent.kind() == "return_statement",
-        }
+        })
     }
 
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "binary_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "binary_expression")?;
 
-        Self {
-            left: Expression::new(node.c_by_n("left")),
-            op: node.c_by_n("operator").kind().to_string(),
-            right: Expression::new(node.c_by_n("right")),
-            context: Self::build_context(&node),
+        Ok(Self {
+            left: Expression::new(node.c_by_n("left")?, source)?,
+            op: node.c_by_n("operator")?.kind().to_string(),
+            right: Expression::new(node.c_by_n("right")?, source)?,
+            context: Self::build_context(&node)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1113,6 +1144,14 @@ impl<'a> DocBuild<'a> for BinaryExpression {
                 ]));
             }
 
+            // the whole chain lines up with its first operand
+            if b.visual_indent() {
+                return result.push(b.align(
+                    0,
+                    b.group_concat(vec![left_doc, b.softline(), op_doc, b.txt(" "), right_doc]),
+                ));
+            }
+
             // group() using the current line indent level
             if !context.is_a_chaining_inner_node && !context.is_parent_return_statement {
                 return result.push(b.group_concat(vec![
@@ -1146,23 +1185,26 @@ pub struct LocalVariableDeclaration {
 }
 
 impl LocalVariableDeclaration {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "local_variable_declaration");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "local_variable_declaration")?;
 
-        let modifiers = node.try_c_by_k("modifiers").map(|n| Modifiers::new(n));
+        let modifiers = node
+            .try_c_by_k("modifiers")
+            .map(|n| Modifiers::new(n, source))
+            .transpose()?;
         let declarators = node
-            .cs_by_n("declarator")
+            .cs_by_n("declarator")?
             .into_iter()
-            .map(|n| VariableDeclarator::new(n))
-            .collect();
+            .map(|n| VariableDeclarator::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             modifiers,
-            type_: UnannotatedType::new(node.c_by_n("type")),
+            type_: UnannotatedType::new(node.c_by_n("type")?, source)?,
             declarators,
             is_parent_for_statement: node.parent().map_or(false, |n| n.kind() == "for_statement"),
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1206,23 +1248,29 @@ pub struct VariableDeclarator {
 }
 
 impl VariableDeclarator {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "variable_declarator");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "variable_declarator")?;
 
         let mut is_value_child_a_query_node = false;
-        let op = node.try_c_by_k("assignment_operator").map(ValueNode::new);
-        let value = node.try_c_by_n("value").map(|n| {
-            is_value_child_a_query_node = is_query_expression(&n);
-            VariableInitializer::Exp(Expression::new(n))
-        });
+        let op = node
+            .try_c_by_k("assignment_operator")
+            .map(|n| ValueNode::new(n, source))
+            .transpose()?;
+        let value = node
+            .try_c_by_n("value")
+            .map(|n| {
+                is_value_child_a_query_node = is_query_expression(&n);
+                Ok(VariableInitializer::Exp(Expression::new(n, source)?))
+            })
+            .transpose()?;
 
-        Self {
-            name: ValueNode::new(node.c_by_n("name")),
+        Ok(Self {
+            name: ValueNode::new(node.c_by_n("name")?, source)?,
             op,
             value,
             is_value_child_a_query_node,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1264,22 +1312,22 @@ pub struct GenericType {
 }
 
 impl GenericType {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "generic_type");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "generic_type")?;
 
         let generic_identifier = if let Some(t) = node.try_c_by_k("type_identifier") {
-            GenericIdentifier::Type(t.value())
+            GenericIdentifier::Type(t.value(source))
         } else if let Some(s) = node.try_c_by_k("scoped_type_identifier") {
-            GenericIdentifier::Scoped(ScopedTypeIdentifier::new(s))
+            GenericIdentifier::Scoped(ScopedTypeIdentifier::new(s, source)?)
         } else {
-            panic!("## can't build generic_identifier node in GenericType");
+            return Err(EnrichError::new(&node, "missing generic identifier"));
         };
 
-        Self {
+        Ok(Self {
             generic_identifier,
-            type_arguments: TypeArguments::new(node.c_by_k("type_arguments")),
+            type_arguments: TypeArguments::new(node.c_by_k("type_arguments")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1319,17 +1367,20 @@ pub struct IfStatement {
 }
 
 impl IfStatement {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "if_statement");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "if_statement")?;
 
-        let alternative = node.try_c_by_n("alternative").map(|a| Statement::new(a));
+        let alternative = node
+            .try_c_by_n("alternative")
+            .map(|a| Statement::new(a, source))
+            .transpose()?;
 
-        Self {
-            condition: ParenthesizedExpression::new(node.c_by_n("condition")),
-            consequence: Statement::new(node.c_by_n("consequence")),
+        Ok(Self {
+            condition: ParenthesizedExpression::new(node.c_by_n("condition")?, source)?,
+            consequence: Statement::new(node.c_by_n("consequence")?, source)?,
             alternative,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1392,11 +1443,11 @@ pub struct ParenthesizedExpression {
 }
 
 impl ParenthesizedExpression {
-    pub fn new(node: Node) -> Self {
-        Self {
-            exp: Expression::new(node.first_c()),
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(Self {
+            exp: Expression::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1423,18 +1474,20 @@ pub enum ForInitOption {
 }
 
 impl ForInitOption {
-    pub fn new(node: Node) -> Self {
-        match node.kind() {
-            "local_variable_declaration" => Self::Declaration(LocalVariableDeclaration::new(node)),
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match node.kind() {
+            "local_variable_declaration" => {
+                Self::Declaration(LocalVariableDeclaration::new(node, source)?)
+            }
             _ => Self::Exps(
                 node.parent()
                     .expect("node must have parent in ForInitOption")
-                    .cs_by_n("init")
+                    .cs_by_n("init")?
                     .into_iter()
-                    .map(|n| Expression::new(n))
-                    .collect(),
+                    .map(|n| Expression::new(n, source))
+                    .collect::<Result<_, _>>()?,
             ),
-        }
+        })
     }
 }
 
@@ -1464,20 +1517,29 @@ pub struct ForStatement {
 }
 
 impl ForStatement {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "for_statement");
-
-        let init = node.try_c_by_n("init").map(|n| ForInitOption::new(n));
-        let condition = node.try_c_by_n("condition").map(|n| Expression::new(n));
-        let update = node.try_c_by_n("update").map(|n| Expression::new(n));
-
-        Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "for_statement")?;
+
+        let init = node
+            .try_c_by_n("init")
+            .map(|n| ForInitOption::new(n, source))
+            .transpose()?;
+        let condition = node
+            .try_c_by_n("condition")
+            .map(|n| Expression::new(n, source))
+            .transpose()?;
+        let update = node
+            .try_c_by_n("update")
+            .map(|n| Expression::new(n, source))
+            .transpose()?;
+
+        Ok(Self {
             init,
             condition,
             update,
-            body: Statement::new(node.c_by_n("body")),
+            body: Statement::new(node.c_by_n("body")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1529,19 +1591,22 @@ pub struct EnhancedForStatement {
 }
 
 impl EnhancedForStatement {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "enhanced_for_statement");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "enhanced_for_statement")?;
 
-        let modifiers = node.try_c_by_k("modifiers").map(|n| Modifiers::new(n));
+        let modifiers = node
+            .try_c_by_k("modifiers")
+            .map(|n| Modifiers::new(n, source))
+            .transpose()?;
 
-        Self {
+        Ok(Self {
             modifiers,
-            type_: UnannotatedType::new(node.c_by_n("type")),
-            name: ValueNode::new(node.c_by_n("name")),
-            value: Expression::new(node.c_by_n("value")),
-            body: Statement::new(node.c_by_n("body")),
+            type_: UnannotatedType::new(node.c_by_n("type")?, source)?,
+            name: ValueNode::new(node.c_by_n("name")?, source)?,
+            value: Expression::new(node.c_by_n("value")?, source)?,
+            body: Statement::new(node.c_by_n("body")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1578,23 +1643,23 @@ pub enum UpdateExpressionVariant {
 }
 
 impl UpdateExpressionVariant {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "update_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "update_expression")?;
 
-        let operator_node = node.c_by_n("operator");
-        let operand_node = node.c_by_n("operand");
+        let operator_node = node.c_by_n("operator")?;
+        let operand_node = node.c_by_n("operand")?;
 
-        if operator_node.start_byte() < operand_node.start_byte() {
+        Ok(if operator_node.start_byte() < operand_node.start_byte() {
             Self::Pre {
-                operator: operator_node.value(),
-                operand: Box::new(Expression::new(operand_node)),
+                operator: operator_node.value(source),
+                operand: Box::new(Expression::new(operand_node, source)?),
             }
         } else {
             Self::Post {
-                operand: Box::new(Expression::new(operand_node)),
-                operator: operator_node.value(),
+                operand: Box::new(Expression::new(operand_node, source)?),
+                operator: operator_node.value(source),
             }
-        }
+        })
     }
 }
 
@@ -1622,34 +1687,37 @@ pub struct ScopedTypeIdentifier {
 }
 
 impl ScopedTypeIdentifier {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "scoped_type_identifier");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "scoped_type_identifier")?;
 
-        let prefix_node = node.first_c();
+        let prefix_node = node.first_c()?;
         let scoped_choice = match prefix_node.kind() {
-            "type_identifier" => ScopedChoice::TypeIdentifier(prefix_node.value()),
-            "scoped_type_identifier" => ScopedChoice::Scoped(Box::new(Self::new(prefix_node))),
-            "generic_type" => ScopedChoice::Generic(Box::new(GenericType::new(prefix_node))),
-            _ => panic_unknown_node(prefix_node, "ScopedTypeIdentifier"),
+            "type_identifier" => ScopedChoice::TypeIdentifier(prefix_node.value(source)),
+            "scoped_type_identifier" => {
+                ScopedChoice::Scoped(Box::new(Self::new(prefix_node, source)?))
+            }
+            "generic_type" => {
+                ScopedChoice::Generic(Box::new(GenericType::new(prefix_node, source)?))
+            }
+            _ => return Err(unknown_node(prefix_node, "ScopedTypeIdentifier")),
         };
 
         let annotations: Vec<_> = node
             .try_cs_by_k("annotation")
             .into_iter()
-            .map(|n| Annotation::new(n))
-            .collect();
+            .map(|n| Annotation::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        let type_identifier_node = node
-            .cs_by_k("type_identifier")
-            .pop()
-            .expect("## mandatory node type_identifier missing in ScopedTypeIdentifier");
+        let type_identifier_node = node.cs_by_k("type_identifier")?.pop().ok_or_else(|| {
+            EnrichError::new(&node, "missing mandatory kind child `type_identifier`")
+        })?;
 
-        Self {
+        Ok(Self {
             scoped_choice,
             annotations,
-            type_identifier: type_identifier_node.value(),
+            type_identifier: type_identifier_node.value(source),
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1703,20 +1771,24 @@ pub struct ConstructorDeclaration {
 }
 
 impl ConstructorDeclaration {
-    pub fn new(node: Node) -> Self {
-        let modifiers = node.try_c_by_k("modifiers").map(|n| Modifiers::new(n));
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        let modifiers = node
+            .try_c_by_k("modifiers")
+            .map(|n| Modifiers::new(n, source))
+            .transpose()?;
         let type_parameters = node
             .try_c_by_k("type_parameters")
-            .map(|n| TypeParameters::new(n));
+            .map(|n| TypeParameters::new(n, source))
+            .transpose()?;
 
-        Self {
+        Ok(Self {
             modifiers,
             type_parameters,
-            name: ValueNode::new(node.c_by_n("name")),
-            parameters: FormalParameters::new(node.c_by_n("parameters")),
-            body: ConstructorBody::new(node.c_by_n("body")),
+            name: ValueNode::new(node.c_by_n("name")?, source)?,
+            parameters: FormalParameters::new(node.c_by_n("parameters")?, source)?,
+            body: ConstructorBody::new(node.c_by_n("body")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1746,32 +1818,30 @@ pub struct ConstructorBody {
 }
 
 impl ConstructorBody {
-    pub fn new(node: Node) -> Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
         let mut constructor_invocation = None;
         let mut statements: Vec<BodyMember<Statement>> = Vec::new();
 
         for (i, c) in node.children_vec().into_iter().enumerate() {
             if i == 0 && c.kind() == "explicit_constructor_invocation" {
-                constructor_invocation = Some(BodyMember::new(&c, ConstructInvocation::new(c)));
+                constructor_invocation =
+                    Some(BodyMember::new(&c, ConstructInvocation::new(c, source)?));
             } else {
-                statements.push(BodyMember::new(&c, Statement::new(c)));
+                statements.push(BodyMember::new(&c, Statement::new(c, source)?));
             }
         }
 
-        Self {
+        Ok(Self {
             constructor_invocation,
             statements,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
 impl<'a> DocBuild<'a> for ConstructorBody {
     fn build_inner(&self, b: &'a DocBuilder<'a>, result: &mut Vec<DocRef<'a>>) {
-        let bucket = get_comment_bucket(&self.node_info.id);
-        handle_pre_comments(b, bucket, result);
-
-        if bucket.dangling_comments.is_empty() {
+        build_body_with_comments(b, &self.node_info.id, result, |b, result| {
             if self.constructor_invocation.is_none() && self.statements.is_empty() {
                 return result.push(b.concat(vec![b.txt("{"), b.nl(), b.txt("}")]));
             }
@@ -1782,7 +1852,7 @@ impl<'a> DocBuild<'a> for ConstructorBody {
                 result.push(b.indent(b.concat(vec![b.nl(), c.member.build(b), b.txt(";")])));
 
                 if !self.statements.is_empty() {
-                    if c.has_trailing_newline {
+                    if c.has_trailing_newline(b) {
                         result.push(b.nl_with_no_indent());
                     }
                     result.push(b.nl());
@@ -1794,9 +1864,7 @@ impl<'a> DocBuild<'a> for ConstructorBody {
             result.push(b.indent(b.intersperse_body_members(&self.statements)));
             result.push(b.nl());
             result.push(b.txt("}"));
-        } else {
-            handle_dangling_comments_in_bracket_surround(b, bucket, result);
-        }
+        });
     }
 }
 
@@ -1810,28 +1878,33 @@ pub struct ConstructInvocation {
 }
 
 impl ConstructInvocation {
-    pub fn new(node: Node) -> Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
         let object = node
             .try_c_by_n("object")
-            .map(|n| Box::new(PrimaryExpression::new(n)));
+            .map(|n| Ok(Box::new(PrimaryExpression::new(n, source)?)))
+            .transpose()?;
 
         let type_arguments = node
             .try_c_by_k("type_arguments")
-            .map(|n| TypeArguments::new(n));
-
-        let constructor = node.try_c_by_n("constructor").map(|n| match n.kind() {
-            "this" => Constructor::This,
-            "super" => Constructor::Super,
-            _ => panic_unknown_node(n, "Constructor"),
-        });
+            .map(|n| TypeArguments::new(n, source))
+            .transpose()?;
+
+        let constructor = node
+            .try_c_by_n("constructor")
+            .map(|n| match n.kind() {
+                "this" => Ok(Constructor::This),
+                "super" => Ok(Constructor::Super),
+                _ => Err(unknown_node(n, "Constructor")),
+            })
+            .transpose()?;
 
-        Self {
+        Ok(Self {
             object,
             type_arguments,
             constructor,
-            arguments: ArgumentList::new(node.c_by_n("arguments")),
+            arguments: ArgumentList::new(node.c_by_n("arguments")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1877,19 +1950,19 @@ pub struct TypeParameters {
 }
 
 impl TypeParameters {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "type_parameters");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "type_parameters")?;
 
         let type_parameters: Vec<_> = node
-            .cs_by_k("type_parameter")
+            .cs_by_k("type_parameter")?
             .into_iter()
-            .map(|n| TypeParameter::new(n))
-            .collect();
+            .map(|n| TypeParameter::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             type_parameters,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1915,18 +1988,18 @@ pub struct TypeParameter {
 }
 
 impl TypeParameter {
-    pub fn new(node: Node) -> Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
         let annotations: Vec<_> = node
             .try_cs_by_k("annotation")
             .into_iter()
-            .map(|n| Annotation::new(n))
-            .collect();
+            .map(|n| Annotation::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             annotations,
-            type_identifier: node.cvalue_by_k("type_identifier"),
+            type_identifier: node.cvalue_by_k("type_identifier", source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1954,21 +2027,25 @@ pub struct ObjectCreationExpression {
 }
 
 impl ObjectCreationExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "object_creation_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "object_creation_expression")?;
 
         let type_arguments = node
             .try_c_by_k("type_arguments")
-            .map(|n| TypeArguments::new(n));
-        let class_body = node.try_c_by_k("class_body").map(|n| ClassBody::new(n));
-
-        Self {
+            .map(|n| TypeArguments::new(n, source))
+            .transpose()?;
+        let class_body = node
+            .try_c_by_k("class_body")
+            .map(|n| ClassBody::new(n, source))
+            .transpose()?;
+
+        Ok(Self {
             type_arguments,
-            type_: UnannotatedType::new(node.c_by_n("type")),
-            arguments: ArgumentList::new(node.c_by_n("arguments")),
+            type_: UnannotatedType::new(node.c_by_n("type")?, source)?,
+            arguments: ArgumentList::new(node.c_by_n("arguments")?, source)?,
             class_body,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1999,14 +2076,14 @@ pub struct RunAsStatement {
 }
 
 impl RunAsStatement {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "run_as_statement");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "run_as_statement")?;
 
-        Self {
-            user: ParenthesizedExpression::new(node.c_by_n("user")),
-            block: Block::new(node.c_by_k("block")),
+        Ok(Self {
+            user: ParenthesizedExpression::new(node.c_by_n("user")?, source)?,
+            block: Block::new(node.c_by_k("block")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2029,15 +2106,15 @@ pub struct DoStatement {
 }
 
 impl DoStatement {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "do_statement");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "do_statement")?;
 
-        Self {
-            body: Block::new(node.c_by_n("body")),
-            condition: ParenthesizedExpression::new(node.c_by_n("condition")),
+        Ok(Self {
+            body: Block::new(node.c_by_n("body")?, source)?,
+            condition: ParenthesizedExpression::new(node.c_by_n("condition")?, source)?,
 
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2061,14 +2138,14 @@ pub struct WhileStatement {
 }
 
 impl WhileStatement {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "while_statement");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "while_statement")?;
 
-        Self {
-            condition: ParenthesizedExpression::new(node.c_by_n("condition")),
-            body: Statement::new(node.c_by_n("body")),
+        Ok(Self {
+            condition: ParenthesizedExpression::new(node.c_by_n("condition")?, source)?,
+            body: Statement::new(node.c_by_n("body")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2097,15 +2174,15 @@ pub struct UnaryExpression {
 }
 
 impl UnaryExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "unary_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "unary_expression")?;
 
-        let operator = node.cvalue_by_n("operator");
-        Self {
+        let operator = node.cvalue_by_n("operator", source)?;
+        Ok(Self {
             operator,
-            operand: Box::new(Expression::new(node.c_by_n("operand"))),
+            operand: Box::new(Expression::new(node.c_by_n("operand")?, source)?),
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2128,33 +2205,36 @@ pub struct FieldAccess {
 }
 
 impl FieldAccess {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "field_access");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "field_access")?;
 
-        let obj_node = node.c_by_n("object");
+        let obj_node = node.c_by_n("object")?;
         let object = if obj_node.kind() == "super" {
-            MethodObject::Super(Super::new(obj_node))
+            MethodObject::Super(Super::new(obj_node, source)?)
         } else {
-            MethodObject::Primary(Box::new(PrimaryExpression::new(obj_node)))
+            MethodObject::Primary(Box::new(PrimaryExpression::new(obj_node, source)?))
         };
 
-        Self {
+        Ok(Self {
             object,
-            property_navigation: Self::get_property_navigation(&node),
-            field: FieldOption::new(node.c_by_n("field")),
+            property_navigation: Self::get_property_navigation(&node, source)?,
+            field: FieldOption::new(node.c_by_n("field")?, source)?,
             context: build_chaining_context(&node),
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 
-    fn get_property_navigation(parent_node: &Node) -> PropertyNavigation {
+    fn get_property_navigation(
+        parent_node: &Node,
+        source: &str,
+    ) -> Result<PropertyNavigation, EnrichError> {
         let property_navigation =
             if let Some(n) = parent_node.try_c_by_k("safe_navigation_operator") {
-                PropertyNavigation::Safe(SafeNavigationOperator::new(n))
+                PropertyNavigation::Safe(SafeNavigationOperator::new(n, source)?)
             } else {
                 PropertyNavigation::Dot
             };
-        property_navigation
+        Ok(property_navigation)
     }
 }
 
@@ -2194,11 +2274,11 @@ pub enum FieldOption {
 }
 
 impl FieldOption {
-    pub fn new(node: Node) -> Self {
-        match node.kind() {
-            "this" => Self::This(This::new(node)),
-            _ => Self::Identifier(ValueNode::new(node)),
-        }
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match node.kind() {
+            "this" => Self::This(This::new(node, source)?),
+            _ => Self::Identifier(ValueNode::new(node, source)?),
+        })
     }
 }
 
@@ -2225,17 +2305,23 @@ pub struct EnumDeclaration {
 }
 
 impl EnumDeclaration {
-    pub fn new(node: Node) -> Self {
-        let modifiers = node.try_c_by_k("modifiers").map(|n| Modifiers::new(n));
-        let interface = node.try_c_by_k("interfaces").map(|n| Interface::new(n));
-
-        Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        let modifiers = node
+            .try_c_by_k("modifiers")
+            .map(|n| Modifiers::new(n, source))
+            .transpose()?;
+        let interface = node
+            .try_c_by_k("interfaces")
+            .map(|n| Interface::new(n, source))
+            .transpose()?;
+
+        Ok(Self {
             modifiers,
-            name: ValueNode::new(node.c_by_n("name")),
+            name: ValueNode::new(node.c_by_n("name")?, source)?,
             interface,
-            body: EnumBody::new(node.c_by_n("body")),
+            body: EnumBody::new(node.c_by_n("body")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2264,28 +2350,25 @@ pub struct EnumBody {
 }
 
 impl EnumBody {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "enum_body");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "enum_body")?;
 
         let enum_constants = node
             .try_cs_by_k("enum_constant")
             .into_iter()
-            .map(|n| EnumConstant::new(n))
-            .collect();
+            .map(|n| EnumConstant::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             enum_constants,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
 impl<'a> DocBuild<'a> for EnumBody {
     fn build_inner(&self, b: &'a DocBuilder<'a>, result: &mut Vec<DocRef<'a>>) {
-        let bucket = get_comment_bucket(&self.node_info.id);
-        handle_pre_comments(b, bucket, result);
-
-        if bucket.dangling_comments.is_empty() {
+        build_body_with_comments(b, &self.node_info.id, result, |b, result| {
             let docs = b.to_docs(&self.enum_constants);
 
             if docs.is_empty() {
@@ -2297,10 +2380,7 @@ impl<'a> DocBuild<'a> for EnumBody {
             let close = Insertable::new(Some(b.nl()), Some("}"), None);
             let doc = b.group_surround(&docs, sep, open, close);
             result.push(doc);
-            handle_post_comments(b, bucket, result);
-        } else {
-            handle_dangling_comments_in_bracket_surround(b, bucket, result);
-        }
+        });
     }
 }
 
@@ -2312,15 +2392,18 @@ pub struct EnumConstant {
 }
 
 impl EnumConstant {
-    pub fn new(node: Node) -> Self {
-        let modifiers = node.try_c_by_k("modifiers").map(|n| Modifiers::new(n));
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        let modifiers = node
+            .try_c_by_k("modifiers")
+            .map(|n| Modifiers::new(n, source))
+            .transpose()?;
 
-        Self {
+        Ok(Self {
             modifiers,
-            name: ValueNode::new(node.c_by_n("name")),
+            name: ValueNode::new(node.c_by_n("name")?, source)?,
 
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2357,24 +2440,26 @@ pub enum DmlExpressionVariant {
 }
 
 impl DmlExpressionVariant {
-    pub fn new(node: Node) -> Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
         let security_mode = node
             .try_c_by_k("dml_security_mode")
-            .map(|n| DmlSecurityMode::new(n));
-        let target = Expression::new(node.c_by_n("target"));
+            .map(|n| DmlSecurityMode::new(n, source))
+            .transpose()?;
+        let target = Expression::new(node.c_by_n("target")?, source)?;
 
-        let dml_type = DmlType::new(node.c_by_k("dml_type"));
-        match dml_type.variant {
+        let dml_type = DmlType::new(node.c_by_k("dml_type")?, source)?;
+        Ok(match dml_type.variant {
             DmlTypeVariant::Merge => Self::Merge {
                 dml_type,
                 security_mode,
                 target,
-                merge_with: Expression::new(node.c_by_n("merge_with")),
+                merge_with: Expression::new(node.c_by_n("merge_with")?, source)?,
             },
             DmlTypeVariant::Upsert => {
                 let unannotated = node
                     .try_c_by_n("upsert_key")
-                    .map(|n| Box::new(UnannotatedType::new(n)));
+                    .map(|n| Ok(Box::new(UnannotatedType::new(n, source)?)))
+                    .transpose()?;
                 Self::Upsert {
                     dml_type,
                     security_mode,
@@ -2387,7 +2472,7 @@ impl DmlExpressionVariant {
                 security_mode,
                 target,
             },
-        }
+        })
     }
 }
 
@@ -2461,13 +2546,13 @@ pub enum DmlSecurityMode {
 }
 
 impl DmlSecurityMode {
-    pub fn new(n: Node) -> Self {
-        let child = n.first_c();
-        match child.kind() {
-            "user" => Self::User(child.value()),
-            "system" => Self::System(child.value()),
-            _ => panic_unknown_node(n, "DmlSecurityMode"),
-        }
+    pub fn new(n: Node, source: &str) -> Result<Self, EnrichError> {
+        let child = n.first_c()?;
+        Ok(match child.kind() {
+            "user" => Self::User(child.value(source)),
+            "system" => Self::System(child.value(source)),
+            _ => return Err(unknown_node(n, "DmlSecurityMode")),
+        })
     }
 }
 
@@ -2492,17 +2577,17 @@ pub enum DmlTypeVariant {
 }
 
 impl DmlTypeVariant {
-    pub fn new(node: Node) -> Self {
+    pub fn new(node: Node, _source: &str) -> Result<Self, EnrichError> {
         let k = node.kind();
-        match k {
+        Ok(match k {
             "insert" => Self::Insert,
             "update" => Self::Update,
             "delete" => Self::Delete,
             "undelete" => Self::Undelete,
             "merge" => Self::Merge,
             "upsert" => Self::Upsert,
-            _ => panic!("## unknown node: {} in DmlTypeVariant ", red(k)),
-        }
+            _ => return Err(unknown_node(node, "DmlTypeVariant")),
+        })
     }
 }
 
@@ -2528,14 +2613,14 @@ pub struct ArrayAccess {
 }
 
 impl ArrayAccess {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "array_access");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "array_access")?;
 
-        Self {
-            array: PrimaryExpression::new(node.c_by_n("array")),
-            index: Expression::new(node.c_by_n("index")),
+        Ok(Self {
+            array: PrimaryExpression::new(node.c_by_n("array")?, source)?,
+            index: Expression::new(node.c_by_n("index")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2558,8 +2643,8 @@ pub struct ArrayCreationExpression {
 }
 
 impl ArrayCreationExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "array_creation_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "array_creation_expression")?;
 
         let value_node = node.try_c_by_n("value");
         let dimensions_node = node.try_c_by_n("dimensions");
@@ -2567,32 +2652,35 @@ impl ArrayCreationExpression {
         let variant = if value_node.is_none() {
             // DD
             let dimensions_exprs = node
-                .cs_by_k("dimensions_expr")
+                .cs_by_k("dimensions_expr")?
                 .into_iter()
-                .map(|n| DimensionsExpr::new(n))
-                .collect();
-            let dimensions = node.try_c_by_k("dimensions").map(|n| Dimensions::new(n));
+                .map(|n| DimensionsExpr::new(n, source))
+                .collect::<Result<_, _>>()?;
+            let dimensions = node
+                .try_c_by_k("dimensions")
+                .map(|n| Dimensions::new(n, source))
+                .transpose()?;
             ArrayCreationVariant::DD {
                 dimensions_exprs,
                 dimensions,
             }
         } else if dimensions_node.is_none() {
             //OnlyV
-            let value = ArrayInitializer::new(node.c_by_n("value"));
+            let value = ArrayInitializer::new(node.c_by_n("value")?, source)?;
             ArrayCreationVariant::OnlyV { value }
         } else {
             //DV
             ArrayCreationVariant::DV {
-                value: ArrayInitializer::new(value_node.unwrap()),
-                dimensions: Dimensions::new(dimensions_node.unwrap()),
+                value: ArrayInitializer::new(value_node.unwrap(), source)?,
+                dimensions: Dimensions::new(dimensions_node.unwrap(), source)?,
             }
         };
 
-        Self {
-            type_: SimpleType::new(node.c_by_n("type")),
+        Ok(Self {
+            type_: SimpleType::new(node.c_by_n("type")?, source)?,
             variant,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2654,12 +2742,12 @@ pub struct Dimensions {
 }
 
 impl Dimensions {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "dimensions");
+    pub fn new(node: Node, _source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "dimensions")?;
 
-        Self {
+        Ok(Self {
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2678,13 +2766,13 @@ pub struct DimensionsExpr {
 }
 
 impl DimensionsExpr {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "dimensions_expr");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "dimensions_expr")?;
 
-        Self {
-            exp: Expression::new(node.first_c()),
+        Ok(Self {
+            exp: Expression::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2705,13 +2793,16 @@ pub struct ReturnStatement {
 }
 
 impl ReturnStatement {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "return_statement");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "return_statement")?;
 
-        Self {
-            exp: node.try_first_c().map(|n| Expression::new(n)),
+        Ok(Self {
+            exp: node
+                .try_first_c()
+                .map(|n| Expression::new(n, source))
+                .transpose()?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2737,15 +2828,15 @@ pub struct TernaryExpression {
 }
 
 impl TernaryExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "ternary_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "ternary_expression")?;
 
-        Self {
-            condition: Expression::new(node.c_by_n("condition")),
-            consequence: Expression::new(node.c_by_n("consequence")),
-            alternative: Expression::new(node.c_by_n("alternative")),
+        Ok(Self {
+            condition: Expression::new(node.c_by_n("condition")?, source)?,
+            consequence: Expression::new(node.c_by_n("consequence")?, source)?,
+            alternative: Expression::new(node.c_by_n("alternative")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2774,30 +2865,30 @@ pub struct TryStatement {
 }
 
 impl TryStatement {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "try_statement");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "try_statement")?;
 
         let tail = if node.try_c_by_k("finally_clause").is_some() {
             TryStatementTail::CatchesFinally(
                 node.try_cs_by_k("catch_clause")
                     .into_iter()
-                    .map(|n| CatchClause::new(n))
-                    .collect(),
-                FinallyClause::new(node.c_by_k("finally_clause")),
+                    .map(|n| CatchClause::new(n, source))
+                    .collect::<Result<_, _>>()?,
+                FinallyClause::new(node.c_by_k("finally_clause")?, source)?,
             )
         } else {
             TryStatementTail::Catches(
-                node.cs_by_k("catch_clause")
+                node.cs_by_k("catch_clause")?
                     .into_iter()
-                    .map(|n| CatchClause::new(n))
-                    .collect(),
+                    .map(|n| CatchClause::new(n, source))
+                    .collect::<Result<_, _>>()?,
             )
         };
-        Self {
-            body: Block::new(node.c_by_n("body")),
+        Ok(Self {
+            body: Block::new(node.c_by_n("body")?, source)?,
             tail,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2843,14 +2934,14 @@ pub struct CatchClause {
 }
 
 impl CatchClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "catch_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "catch_clause")?;
 
-        Self {
-            formal_parameter: FormalParameter::new(node.c_by_k("formal_parameter")),
-            body: Block::new(node.c_by_n("body")),
+        Ok(Self {
+            formal_parameter: FormalParameter::new(node.c_by_k("formal_parameter")?, source)?,
+            body: Block::new(node.c_by_n("body")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2874,13 +2965,13 @@ pub struct FinallyClause {
 }
 
 impl FinallyClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "finally_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "finally_clause")?;
 
-        Self {
-            body: Block::new(node.c_by_k("block")),
+        Ok(Self {
+            body: Block::new(node.c_by_k("block")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2900,11 +2991,11 @@ pub struct StaticInitializer {
 }
 
 impl StaticInitializer {
-    pub fn new(node: Node) -> Self {
-        Self {
-            block: Block::new(node.c_by_k("block")),
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(Self {
+            block: Block::new(node.c_by_k("block")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2928,25 +3019,30 @@ pub struct InterfaceDeclaration {
 }
 
 impl InterfaceDeclaration {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "interface_declaration");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "interface_declaration")?;
 
-        let modifiers = node.try_c_by_k("modifiers").map(|n| Modifiers::new(n));
+        let modifiers = node
+            .try_c_by_k("modifiers")
+            .map(|n| Modifiers::new(n, source))
+            .transpose()?;
         let type_parameters = node
             .try_c_by_k("type_parameters")
-            .map(|n| TypeParameters::new(n));
+            .map(|n| TypeParameters::new(n, source))
+            .transpose()?;
         let extends = node
             .try_c_by_k("extends_interfaces")
-            .map(|n| ExtendsInterface::new(n));
+            .map(|n| ExtendsInterface::new(n, source))
+            .transpose()?;
 
-        Self {
+        Ok(Self {
             modifiers,
-            name: ValueNode::new(node.c_by_n("name")),
+            name: ValueNode::new(node.c_by_n("name")?, source)?,
             type_parameters,
             extends,
-            body: InterfaceBody::new(node.c_by_n("body")),
+            body: InterfaceBody::new(node.c_by_n("body")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -2980,11 +3076,11 @@ pub struct ExtendsInterface {
 }
 
 impl ExtendsInterface {
-    pub fn new(node: Node) -> Self {
-        Self {
-            type_list: TypeList::new(node.c_by_k("type_list")),
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(Self {
+            type_list: TypeList::new(node.c_by_k("type_list")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3005,8 +3101,8 @@ pub struct InterfaceBody {
 }
 
 impl InterfaceBody {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "interface_body");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "interface_body")?;
 
         let members: Vec<_> = node
             .children_vec()
@@ -3014,39 +3110,37 @@ impl InterfaceBody {
             .map(|n| {
                 let member = match n.kind() {
                     "constant_declaration" => {
-                        InterfaceMember::Constant(ConstantDeclaration::new(n))
+                        InterfaceMember::Constant(ConstantDeclaration::new(n, source)?)
+                    }
+                    "enum_declaration" => InterfaceMember::EnumD(EnumDeclaration::new(n, source)?),
+                    "method_declaration" => {
+                        InterfaceMember::Method(MethodDeclaration::new(n, source)?)
+                    }
+                    "class_declaration" => {
+                        InterfaceMember::Class(ClassDeclaration::new(n, source)?)
                     }
-                    "enum_declaration" => InterfaceMember::EnumD(EnumDeclaration::new(n)),
-                    "method_declaration" => InterfaceMember::Method(MethodDeclaration::new(n)),
-                    "class_declaration" => InterfaceMember::Class(ClassDeclaration::new(n)),
                     "interface_declaration" => {
-                        InterfaceMember::Interface(InterfaceDeclaration::new(n))
+                        InterfaceMember::Interface(InterfaceDeclaration::new(n, source)?)
                     }
-                    _ => panic_unknown_node(n, "InterfaceBody"),
+                    _ => return Err(unknown_node(n, "InterfaceBody")),
                 };
 
-                BodyMember::new(&n, member)
+                Ok(BodyMember::new(&n, member))
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             members,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
 impl<'a> DocBuild<'a> for InterfaceBody {
     fn build_inner(&self, b: &'a DocBuilder<'a>, result: &mut Vec<DocRef<'a>>) {
-        let bucket = get_comment_bucket(&self.node_info.id);
-        handle_pre_comments(b, bucket, result);
-
-        if bucket.dangling_comments.is_empty() {
+        build_body_with_comments(b, &self.node_info.id, result, |b, result| {
             result.push(b.surround_body_members(&self.members, "{", "}"));
-            handle_post_comments(b, bucket, result);
-        } else {
-            handle_dangling_comments_in_bracket_surround(b, bucket, result);
-        }
+        });
     }
 }
 
@@ -3094,20 +3188,23 @@ pub struct ConstantDeclaration {
 }
 
 impl ConstantDeclaration {
-    pub fn new(node: Node) -> Self {
-        let modifiers = node.try_c_by_k("modifiers").map(|n| Modifiers::new(n));
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        let modifiers = node
+            .try_c_by_k("modifiers")
+            .map(|n| Modifiers::new(n, source))
+            .transpose()?;
         let declarators = node
-            .cs_by_n("declarator")
+            .cs_by_n("declarator")?
             .into_iter()
-            .map(|n| VariableDeclarator::new(n))
-            .collect();
+            .map(|n| VariableDeclarator::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             modifiers,
-            type_: UnannotatedType::new(node.c_by_n("type")),
+            type_: UnannotatedType::new(node.c_by_n("type")?, source)?,
             declarators,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3138,21 +3235,21 @@ pub struct AccessorList {
 }
 
 impl AccessorList {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "accessor_list");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "accessor_list")?;
 
         let accessor_declarations: Vec<_> = node
-            .cs_by_k("accessor_declaration")
+            .cs_by_k("accessor_declaration")?
             .into_iter()
-            .map(|n| AccessorDeclaration::new(n))
-            .collect();
+            .map(|n| AccessorDeclaration::new(n, source))
+            .collect::<Result<_, _>>()?;
         let child_has_body_section = accessor_declarations.iter().any(|n| n.body.is_some());
 
-        Self {
+        Ok(Self {
             accessor_declarations,
             child_has_body_section,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3194,16 +3291,22 @@ pub struct AccessorDeclaration {
 }
 
 impl AccessorDeclaration {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "accessor_declaration");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "accessor_declaration")?;
 
-        let modifiers = node.try_c_by_k("modifiers").map(|n| Modifiers::new(n));
-        Self {
+        let modifiers = node
+            .try_c_by_k("modifiers")
+            .map(|n| Modifiers::new(n, source))
+            .transpose()?;
+        Ok(Self {
             modifiers,
-            accessor: node.cvalue_by_n("accessor"),
-            body: node.try_c_by_n("body").map(|n| Block::new(n)),
+            accessor: node.cvalue_by_n("accessor", source)?,
+            body: node
+                .try_c_by_n("body")
+                .map(|n| Block::new(n, source))
+                .transpose()?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3233,14 +3336,14 @@ pub struct CastExpression {
 }
 
 impl CastExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "cast_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "cast_expression")?;
 
-        Self {
-            type_: Type::new(node.c_by_n("type")),
-            value: Expression::new(node.c_by_n("value")),
+        Ok(Self {
+            type_: Type::new(node.c_by_n("type")?, source)?,
+            value: Expression::new(node.c_by_n("value")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3262,13 +3365,13 @@ pub struct ThrowStatement {
 }
 
 impl ThrowStatement {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "throw_statement");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "throw_statement")?;
 
-        Self {
-            exp: Expression::new(node.first_c()),
+        Ok(Self {
+            exp: Expression::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3289,13 +3392,16 @@ pub struct BreakStatement {
 }
 
 impl BreakStatement {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "break_statement");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "break_statement")?;
 
-        Self {
-            identifier: node.try_c_by_k("identifier").map(|n| ValueNode::new(n)),
+        Ok(Self {
+            identifier: node
+                .try_c_by_k("identifier")
+                .map(|n| ValueNode::new(n, source))
+                .transpose()?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3320,13 +3426,16 @@ pub struct ContinueStatement {
 }
 
 impl ContinueStatement {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "continue_statement");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "continue_statement")?;
 
-        Self {
-            identifier: node.try_c_by_k("identifier").map(|n| ValueNode::new(n)),
+        Ok(Self {
+            identifier: node
+                .try_c_by_k("identifier")
+                .map(|n| ValueNode::new(n, source))
+                .transpose()?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3352,14 +3461,14 @@ pub struct SwitchExpression {
 }
 
 impl SwitchExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "switch_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "switch_expression")?;
 
-        Self {
-            condition: Expression::new(node.c_by_n("condition")),
-            body: SwitchBlock::new(node.c_by_n("body")),
+        Ok(Self {
+            condition: Expression::new(node.c_by_n("condition")?, source)?,
+            body: SwitchBlock::new(node.c_by_n("body")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3382,19 +3491,19 @@ pub struct SwitchBlock {
 }
 
 impl SwitchBlock {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "switch_block");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "switch_block")?;
 
         let rules = node
-            .cs_by_k("switch_rule")
+            .cs_by_k("switch_rule")?
             .into_iter()
-            .map(|n| SwitchRule::new(n))
-            .collect();
+            .map(|n| SwitchRule::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             rules,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3420,14 +3529,14 @@ pub struct SwitchRule {
 }
 
 impl SwitchRule {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "switch_rule");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "switch_rule")?;
 
-        Self {
-            label: SwitchLabel::new(node.c_by_k("switch_label")),
-            block: Block::new(node.c_by_k("block")),
+        Ok(Self {
+            label: SwitchLabel::new(node.c_by_k("switch_label")?, source)?,
+            block: Block::new(node.c_by_k("block")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3449,21 +3558,21 @@ pub enum SwitchLabel {
 }
 
 impl SwitchLabel {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "switch_label");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "switch_label")?;
 
-        if node.children_vec().is_empty() {
+        Ok(if node.children_vec().is_empty() {
             Self::Else
         } else if let Some(when_node) = node.try_c_by_k("when_sobject_type") {
-            Self::WhenSObject(WhenSObjectType::new(when_node))
+            Self::WhenSObject(WhenSObjectType::new(when_node, source)?)
         } else {
             let expressions = node
                 .children_vec()
                 .into_iter()
-                .map(Expression::new)
-                .collect();
+                .map(|n| Expression::new(n, source))
+                .collect::<Result<_, _>>()?;
             Self::Expressions(expressions)
-        }
+        })
     }
 }
 
@@ -3495,27 +3604,27 @@ pub struct WhenSObjectType {
 }
 
 impl WhenSObjectType {
-    pub fn new(node: Node) -> Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
         let mut unannotated_type = None;
         let mut identifier = None;
 
         for child in node.children_vec() {
             match child.kind() {
                 "identifier" => {
-                    identifier = Some(child.value());
+                    identifier = Some(child.value(source));
                 }
                 _ => {
-                    unannotated_type = Some(UnannotatedType::new(child));
+                    unannotated_type = Some(UnannotatedType::new(child, source)?);
                 }
             }
         }
 
-        Self {
+        Ok(Self {
             unannotated_type: unannotated_type
-                .expect("Missing unannotated_type in WhenSObjectType"),
-            identifier: identifier.expect("Missing identifier in WhenSObjectType"),
+                .ok_or_else(|| EnrichError::new(&node, "missing unannotated type"))?,
+            identifier: identifier.ok_or_else(|| EnrichError::new(&node, "missing identifier"))?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3537,14 +3646,14 @@ pub struct InstanceOfExpression {
 }
 
 impl InstanceOfExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "instanceof_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "instanceof_expression")?;
 
-        Self {
-            left: Expression::new(node.c_by_n("left")),
-            right: Type::new(node.c_by_n("right")),
+        Ok(Self {
+            left: Expression::new(node.c_by_n("left")?, source)?,
+            right: Type::new(node.c_by_n("right")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3565,14 +3674,17 @@ pub struct VersionExpression {
 }
 
 impl VersionExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "version_expression");
-
-        let version_number = node.try_c_by_n("version_num").map(|n| ValueNode::new(n));
-        Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "version_expression")?;
+
+        let version_number = node
+            .try_c_by_n("version_num")
+            .map(|n| ValueNode::new(n, source))
+            .transpose()?;
+        Ok(Self {
             version_number,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3596,13 +3708,13 @@ pub struct JavaFieldAccess {
 }
 
 impl JavaFieldAccess {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "java_field_access");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "java_field_access")?;
 
-        Self {
-            field_access: FieldAccess::new(node.c_by_k("field_access")),
+        Ok(Self {
+            field_access: FieldAccess::new(node.c_by_k("field_access")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3622,14 +3734,14 @@ pub struct JavaType {
 }
 
 impl JavaType {
-    pub fn new(node: Node) -> Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
         let scoped_type_identifier =
-            ScopedTypeIdentifier::new(node.c_by_k("scoped_type_identifier"));
+            ScopedTypeIdentifier::new(node.c_by_k("scoped_type_identifier")?, source)?;
 
-        Self {
+        Ok(Self {
             scoped_type_identifier,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3650,14 +3762,14 @@ pub struct ArrayType {
 }
 
 impl ArrayType {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "array_type");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "array_type")?;
 
-        Self {
-            element: UnannotatedType::new(node.c_by_n("element")),
-            dimensions: Dimensions::new(node.c_by_n("dimensions")),
+        Ok(Self {
+            element: UnannotatedType::new(node.c_by_n("element")?, source)?,
+            dimensions: Dimensions::new(node.c_by_n("dimensions")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3680,22 +3792,22 @@ pub struct TriggerDeclaration {
 }
 
 impl TriggerDeclaration {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "trigger_declaration");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "trigger_declaration")?;
 
         let events = node
-            .cs_by_k("trigger_event")
+            .cs_by_k("trigger_event")?
             .into_iter()
-            .map(|n| TriggerEvent::new(n))
-            .collect();
+            .map(|n| TriggerEvent::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
-            name: ValueNode::new(node.c_by_n("name")),
-            object: ValueNode::new(node.c_by_n("object")),
+        Ok(Self {
+            name: ValueNode::new(node.c_by_n("name")?, source)?,
+            object: ValueNode::new(node.c_by_n("object")?, source)?,
             events,
-            body: TriggerBody::new(node.c_by_n("body")),
+            body: TriggerBody::new(node.c_by_n("body")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3727,13 +3839,13 @@ pub struct TriggerEvent {
 }
 
 impl TriggerEvent {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "trigger_event");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "trigger_event")?;
 
-        Self {
-            event: TriggerEventVariant::new(node.first_c()),
+        Ok(Self {
+            event: TriggerEventVariant::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3752,13 +3864,13 @@ pub struct TriggerBody {
 }
 
 impl TriggerBody {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "trigger_body");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "trigger_body")?;
 
-        Self {
-            block: Block::new(node.c_by_k("block")),
+        Ok(Self {
+            block: Block::new(node.c_by_k("block")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3778,20 +3890,20 @@ pub struct QueryExpression {
 }
 
 impl QueryExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "query_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "query_expression")?;
 
         let query_body = if let Some(soql_node) = node.try_c_by_k("soql_query_body") {
-            QueryBody::Soql(SoqlQueryBody::new(soql_node))
+            QueryBody::Soql(SoqlQueryBody::new(soql_node, source)?)
         } else {
-            QueryBody::Sosl(SoslQueryBody::new(node.c_by_k("sosl_query_body")))
+            QueryBody::Sosl(SoslQueryBody::new(node.c_by_k("sosl_query_body")?, source)?)
         };
 
-        Self {
+        Ok(Self {
             query_body,
             context: build_chaining_context(&node),
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3857,28 +3969,37 @@ pub struct SoslQueryBody {
 }
 
 impl SoslQueryBody {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "sosl_query_body");
-
-        let find_clause = FindClause::new(node.c_by_k("find_clause"));
-        let in_clause = node.try_c_by_k("in_clause").map(|n| InClause::new(n));
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "sosl_query_body")?;
+
+        let find_clause = FindClause::new(node.c_by_k("find_clause")?, source)?;
+        let in_clause = node
+            .try_c_by_k("in_clause")
+            .map(|n| InClause::new(n, source))
+            .transpose()?;
         let returning_clause = node
             .try_c_by_k("returning_clause")
-            .map(|n| ReturningClause::new(n));
+            .map(|n| ReturningClause::new(n, source))
+            .transpose()?;
         let with_clauses = node
             .try_cs_by_k("with_clause")
             .into_iter()
-            .map(|n| SoslWithClause::new(n))
-            .collect();
+            .map(|n| SoslWithClause::new(n, source))
+            .collect::<Result<_, _>>()?;
         let using_clause = node
             .try_c_by_k("sosl_using_clause")
-            .map(|n| SoslUsingClause::new(n));
-        let limit_clause = node.try_c_by_k("limit_clause").map(|n| LimitClause::new(n));
+            .map(|n| SoslUsingClause::new(n, source))
+            .transpose()?;
+        let limit_clause = node
+            .try_c_by_k("limit_clause")
+            .map(|n| LimitClause::new(n, source))
+            .transpose()?;
         let update_clause = node
             .try_c_by_k("update_clause")
-            .map(|n| UpdateClause::new(n));
+            .map(|n| UpdateClause::new(n, source))
+            .transpose()?;
 
-        Self {
+        Ok(Self {
             find_clause,
             in_clause,
             returning_clause,
@@ -3887,7 +4008,7 @@ impl SoslQueryBody {
             limit_clause,
             update_clause,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3933,14 +4054,16 @@ pub enum FindClause {
 }
 
 impl FindClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "find_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "find_clause")?;
 
-        if let Some(bound_node) = node.try_c_by_k("bound_apex_expression") {
-            Self::Bound(BoundApexExpression::new(bound_node))
-        } else {
-            Self::Term(node.cvalue_by_k("term"))
-        }
+        Ok(
+            if let Some(bound_node) = node.try_c_by_k("bound_apex_expression") {
+                Self::Bound(BoundApexExpression::new(bound_node, source)?)
+            } else {
+                Self::Term(node.cvalue_by_k("term", source)?)
+            },
+        )
     }
 }
 
@@ -3965,13 +4088,13 @@ pub struct InClause {
 }
 
 impl InClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "in_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "in_clause")?;
 
-        Self {
-            in_type: ValueNode::new(node.c_by_k("in_type")),
+        Ok(Self {
+            in_type: ValueNode::new(node.c_by_k("in_type")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -3993,17 +4116,17 @@ pub struct ReturningClause {
 }
 
 impl ReturningClause {
-    pub fn new(node: Node) -> Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
         let sobject_returns = node
-            .cs_by_k("sobject_return")
+            .cs_by_k("sobject_return")?
             .into_iter()
-            .map(|n| SObjectReturn::new(n))
-            .collect();
+            .map(|n| SObjectReturn::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             sobject_returns,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4030,34 +4153,48 @@ pub struct SObjectReturn {
 }
 
 impl SObjectReturn {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "sobject_return");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "sobject_return")?;
 
         let sobject_return_query = node
             .try_c_by_k("selected_fields")
-            .map(|n| SObjectReturnQuery {
-                selected_fields: n
-                    .children_vec()
-                    .into_iter()
-                    .map(|selectable_node| SelectableExpression::new(selectable_node))
-                    .collect(),
-                using_clause: node.try_c_by_k("using_clause").map(|n| UsingClause::new(n)),
-                where_clause: node.try_c_by_k("where_clause").map(|n| WhereClause::new(n)),
-                order_by_clause: node
-                    .try_c_by_k("order_by_clause")
-                    .map(|n| OrderByClause::new(n)),
-                limit_clause: node.try_c_by_k("limit_clause").map(|n| LimitClause::new(n)),
-                offset_clause: node
-                    .try_c_by_k("offset_clause")
-                    .map(|n| OffsetClause::new(n)),
-                node_info: NodeInfo::from(&n),
-            });
-
-        Self {
-            identifier: ValueNode::new(node.c_by_k("identifier")),
+            .map(|n| {
+                Ok(SObjectReturnQuery {
+                    selected_fields: n
+                        .children_vec()
+                        .into_iter()
+                        .map(|n| SelectableExpression::new(n, source))
+                        .collect::<Result<_, _>>()?,
+                    using_clause: node
+                        .try_c_by_k("using_clause")
+                        .map(|n| UsingClause::new(n, source))
+                        .transpose()?,
+                    where_clause: node
+                        .try_c_by_k("where_clause")
+                        .map(|n| WhereClause::new(n, source))
+                        .transpose()?,
+                    order_by_clause: node
+                        .try_c_by_k("order_by_clause")
+                        .map(|n| OrderByClause::new(n, source))
+                        .transpose()?,
+                    limit_clause: node
+                        .try_c_by_k("limit_clause")
+                        .map(|n| LimitClause::new(n, source))
+                        .transpose()?,
+                    offset_clause: node
+                        .try_c_by_k("offset_clause")
+                        .map(|n| OffsetClause::new(n, source))
+                        .transpose()?,
+                    node_info: NodeInfo::from(&n),
+                })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            identifier: ValueNode::new(node.c_by_k("identifier")?, source)?,
             sobject_return_query,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4137,35 +4274,46 @@ pub struct SoqlQueryBody {
 }
 
 impl SoqlQueryBody {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "soql_query_body");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "soql_query_body")?;
 
-        let where_clause = node.try_c_by_n("where_clause").map(|n| WhereClause::new(n));
+        let where_clause = node
+            .try_c_by_n("where_clause")
+            .map(|n| WhereClause::new(n, source))
+            .transpose()?;
         let with_clause = node
             .try_c_by_n("with_clause")
-            .map(|n| SoqlWithClause::new(n));
+            .map(|n| SoqlWithClause::new(n, source))
+            .transpose()?;
         let group_by_clause = node
             .try_c_by_n("group_by_clause")
-            .map(|n| GroupByClause::new(n));
+            .map(|n| GroupByClause::new(n, source))
+            .transpose()?;
         let order_by_clause = node
             .try_c_by_n("order_by_clause")
-            .map(|n| OrderByClause::new(n));
-        let limit_clause = node.try_c_by_n("limit_clause").map(|n| LimitClause::new(n));
+            .map(|n| OrderByClause::new(n, source))
+            .transpose()?;
+        let limit_clause = node
+            .try_c_by_n("limit_clause")
+            .map(|n| LimitClause::new(n, source))
+            .transpose()?;
         let offset_clause = node
             .try_c_by_n("offset_clause")
-            .map(|n| OffsetClause::new(n));
+            .map(|n| OffsetClause::new(n, source))
+            .transpose()?;
         let all_rows_clause = node
             .try_c_by_n("all_rows_clause")
-            .map(|n| AllRowsClause::new(n));
+            .map(|n| AllRowsClause::new(n, source))
+            .transpose()?;
         let for_clause = node
             .try_cs_by_k("for_clause")
             .into_iter()
-            .map(|n| ForClause::new(n))
-            .collect();
+            .map(|n| ForClause::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
-            select_clause: SelectClause::new(node.c_by_n("select_clause")),
-            from_clause: FromClause::new(node.c_by_n("from_clause")),
+        Ok(Self {
+            select_clause: SelectClause::new(node.c_by_n("select_clause")?, source)?,
+            from_clause: FromClause::new(node.c_by_n("from_clause")?, source)?,
             where_clause,
             with_clause,
             group_by_clause,
@@ -4175,7 +4323,7 @@ impl SoqlQueryBody {
             for_clause,
             all_rows_clause,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4231,13 +4379,13 @@ pub struct FromClause {
 }
 
 impl FromClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "from_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "from_clause")?;
 
-        Self {
-            content: StorageVariant::new(node.first_c()),
+        Ok(Self {
+            content: StorageVariant::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4258,14 +4406,14 @@ pub struct StorageAlias {
 }
 
 impl StorageAlias {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "storage_alias");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "storage_alias")?;
 
-        Self {
-            storage_identifier: StorageIdentifier::new(node.c_by_k("storage_identifier")),
-            identifier: ValueNode::new(node.c_by_k("identifier")),
+        Ok(Self {
+            storage_identifier: StorageIdentifier::new(node.c_by_k("storage_identifier")?, source)?,
+            identifier: ValueNode::new(node.c_by_k("identifier")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4286,13 +4434,13 @@ pub struct LimitClause {
 }
 
 impl LimitClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "limit_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "limit_clause")?;
 
-        Self {
-            limit_value: LimitValue::new(node.first_c()),
+        Ok(Self {
+            limit_value: LimitValue::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4312,17 +4460,17 @@ pub struct UpdateClause {
 }
 
 impl UpdateClause {
-    pub fn new(node: Node) -> Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
         let update_types = node
-            .cs_by_k("update_type")
+            .cs_by_k("update_type")?
             .into_iter()
-            .map(|n| ValueNode::new(n))
-            .collect();
+            .map(|n| ValueNode::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             update_types,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4346,13 +4494,13 @@ pub struct BoundApexExpression {
 }
 
 impl BoundApexExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "bound_apex_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "bound_apex_expression")?;
 
-        Self {
-            exp: Box::new(Expression::new(node.first_c())),
+        Ok(Self {
+            exp: Box::new(Expression::new(node.first_c()?, source)?),
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4372,13 +4520,13 @@ pub struct SoslUsingClause {
 }
 
 impl SoslUsingClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "sosl_using_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "sosl_using_clause")?;
 
-        Self {
-            search: UsingSearch::new(node.first_c()),
+        Ok(Self {
+            search: UsingSearch::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4398,12 +4546,12 @@ pub enum UsingSearch {
 }
 
 impl UsingSearch {
-    pub fn new(node: Node) -> Self {
-        match node.kind() {
+    pub fn new(node: Node, _source: &str) -> Result<Self, EnrichError> {
+        Ok(match node.kind() {
             "using_phrase_search" => Self::Phrase,
             "using_advanced_search" => Self::Advanced,
-            _ => panic_unknown_node(node, "UsingSearch"),
-        }
+            _ => return Err(unknown_node(node, "UsingSearch")),
+        })
     }
 }
 
@@ -4427,13 +4575,13 @@ pub struct UsingClause {
 }
 
 impl UsingClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "using_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "using_clause")?;
 
-        Self {
-            option: UsingClauseOption::new(node.first_c()),
+        Ok(Self {
+            option: UsingClauseOption::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4454,13 +4602,13 @@ pub enum UsingClauseOption {
 }
 
 impl UsingClauseOption {
-    pub fn new(node: Node) -> Self {
-        match node.kind() {
-            "using_scope_clause" => Self::Scope(UsingScopeClause::new(node)),
-            "using_lookup_clause" => Self::Lookup(UsingLookupClause::new(node)),
-            "using_listview_clause" => Self::Listview(UsingListviewClause::new(node)),
-            _ => panic_unknown_node(node, "UsingClauseOption"),
-        }
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match node.kind() {
+            "using_scope_clause" => Self::Scope(UsingScopeClause::new(node, source)?),
+            "using_lookup_clause" => Self::Lookup(UsingLookupClause::new(node, source)?),
+            "using_listview_clause" => Self::Listview(UsingListviewClause::new(node, source)?),
+            _ => return Err(unknown_node(node, "UsingClauseOption")),
+        })
     }
 }
 
@@ -4487,13 +4635,13 @@ pub struct UsingScopeClause {
 }
 
 impl UsingScopeClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "using_scope_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "using_scope_clause")?;
 
-        Self {
-            type_: ValueNode::new(node.c_by_k("using_scope_type")),
+        Ok(Self {
+            type_: ValueNode::new(node.c_by_k("using_scope_type")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4514,19 +4662,20 @@ pub struct UsingLookupClause {
 }
 
 impl UsingLookupClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "using_lookup_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "using_lookup_clause")?;
 
-        let lookup_field = DottedIdentifier::new(node.c_by_n("using_lookup_clause"));
+        let lookup_field = DottedIdentifier::new(node.c_by_n("using_lookup_clause")?, source)?;
         let bind_clause = node
             .try_c_by_k("using_lookup_bind_clause")
-            .map(|n| UsingLookupBindClause::new(n));
+            .map(|n| UsingLookupBindClause::new(n, source))
+            .transpose()?;
 
-        Self {
+        Ok(Self {
             lookup_field,
             bind_clause,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4550,13 +4699,13 @@ pub struct UsingListviewClause {
 }
 
 impl UsingListviewClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "using_listview_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "using_listview_clause")?;
 
-        Self {
-            identifier: ValueNode::new(node.c_by_k("identifier")),
+        Ok(Self {
+            identifier: ValueNode::new(node.c_by_k("identifier")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4576,20 +4725,20 @@ pub struct UsingLookupBindClause {
 }
 
 impl UsingLookupBindClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "using_lookup_bind_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "using_lookup_bind_clause")?;
 
         let bind_exps = node
             .try_cs_by_k("using_lookup_bind_expression")
             .into_iter()
-            .map(|n| UsingLookupBindExpression::new(n))
-            .collect();
+            .map(|n| UsingLookupBindExpression::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             bind_exps,
 
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4614,14 +4763,14 @@ pub struct UsingLookupBindExpression {
 }
 
 impl UsingLookupBindExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "using_lookup_bind_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "using_lookup_bind_expression")?;
 
-        Self {
-            field: ValueNode::new(node.c_by_k("field")),
-            bound_value: SoqlLiteral::new(node.c_by_n("bound_value")),
+        Ok(Self {
+            field: ValueNode::new(node.c_by_k("field")?, source)?,
+            bound_value: SoqlLiteral::new(node.c_by_n("bound_value")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4642,19 +4791,25 @@ pub struct WhereClause {
 }
 
 impl WhereClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "where_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "where_clause")?;
 
-        Self {
-            boolean_exp: BooleanExpression::new(node.first_c()),
+        Ok(Self {
+            boolean_exp: BooleanExpression::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
 impl<'a> DocBuild<'a> for WhereClause {
     fn build_inner(&self, b: &'a DocBuilder<'a>, result: &mut Vec<DocRef<'a>>) {
         build_with_comments(b, &self.node_info.id, result, |b, result| {
+            if b.visual_indent() {
+                // continuations line up with the first condition
+                let exp = b.align(0, self.boolean_exp.build_with_parent(b, None));
+                return result.push(b.group_concat(vec![b.txt("WHERE "), exp]));
+            }
+
             let docs = vec![
                 b.txt("WHERE"),
                 b.softline(),
@@ -4673,14 +4828,14 @@ pub struct ComparisonExpression {
 }
 
 impl ComparisonExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "comparison_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "comparison_expression")?;
 
-        Self {
-            value: Box::new(ValueExpression::new(node.first_c())),
-            comparison: get_comparsion(&node),
+        Ok(Self {
+            value: Box::new(ValueExpression::new(node.first_c()?, source)?),
+            comparison: get_comparsion(&node, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4726,17 +4881,17 @@ pub struct ComparableList {
 }
 
 impl ComparableList {
-    pub fn new(node: Node) -> Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
         let values = node
             .children_vec()
             .into_iter()
-            .map(|n| ComparableListValue::new(n))
-            .collect();
+            .map(|n| ComparableListValue::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             values,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4761,19 +4916,19 @@ pub struct OrderByClause {
 }
 
 impl OrderByClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "order_by_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "order_by_clause")?;
 
         let exps = node
-            .cs_by_k("order_expression")
+            .cs_by_k("order_expression")?
             .into_iter()
-            .map(|n| OrderExpression::new(n))
-            .collect();
+            .map(|n| OrderExpression::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             exps,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4799,22 +4954,24 @@ pub struct OrderExpression {
 }
 
 impl OrderExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "order_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "order_expression")?;
 
         let direction = node
             .try_c_by_k("order_direction")
-            .map(|n| ValueNode::new(n));
+            .map(|n| ValueNode::new(n, source))
+            .transpose()?;
         let null_direction = node
             .try_c_by_k("order_null_direction")
-            .map(|n| ValueNode::new(n));
+            .map(|n| ValueNode::new(n, source))
+            .transpose()?;
 
-        Self {
-            value_expression: ValueExpression::new(node.first_c()),
+        Ok(Self {
+            value_expression: ValueExpression::new(node.first_c()?, source)?,
             direction,
             null_direction,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4842,12 +4999,13 @@ pub struct SubQuery {
 }
 
 impl SubQuery {
-    pub fn new(node: Node) -> Self {
-        let soql_query_body = Box::new(SoqlQueryBody::new(node.c_by_k("soql_query_body")));
-        Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        let soql_query_body =
+            Box::new(SoqlQueryBody::new(node.c_by_k("soql_query_body")?, source)?);
+        Ok(Self {
             soql_query_body,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4872,14 +5030,14 @@ pub struct MapCreationExpression {
 }
 
 impl MapCreationExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "map_creation_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "map_creation_expression")?;
 
-        Self {
-            type_: SimpleType::new(node.c_by_n("type")),
-            value: MapInitializer::new(node.c_by_n("value")),
+        Ok(Self {
+            type_: SimpleType::new(node.c_by_n("type")?, source)?,
+            value: MapInitializer::new(node.c_by_n("value")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4900,19 +5058,19 @@ pub struct MapInitializer {
 }
 
 impl MapInitializer {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "map_initializer");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "map_initializer")?;
 
         let initializers: Vec<_> = node
             .children_vec()
             .into_iter()
-            .map(|n| MapKeyInitializer::new(n))
-            .collect();
+            .map(|n| MapKeyInitializer::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             initializers,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4938,19 +5096,19 @@ pub struct MapKeyInitializer {
 }
 
 impl MapKeyInitializer {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "map_key_initializer");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "map_key_initializer")?;
 
         let children = node.children_vec();
         if children.len() != 2 {
-            panic!("### must be exactly 2 child nodes in MapKeyInitializer");
+            return Err(EnrichError::new(&node, "expected exactly 2 child nodes"));
         }
 
-        Self {
-            exp1: Box::new(Expression::new(children[0])),
-            exp2: Box::new(Expression::new(children[1])),
+        Ok(Self {
+            exp1: Box::new(Expression::new(children[0], source)?),
+            exp2: Box::new(Expression::new(children[1], source)?),
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -4972,8 +5130,8 @@ pub struct GroupByClause {
 }
 
 impl GroupByClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "group_by_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "group_by_clause")?;
 
         let mut exps = Vec::new();
         let mut have_clause = None;
@@ -4981,24 +5139,28 @@ impl GroupByClause {
         for child in node.children_vec() {
             match child.kind() {
                 "field_identifier" => {
-                    exps.push(GroupByExpression::Field(FieldIdentifier::new(child)));
+                    exps.push(GroupByExpression::Field(FieldIdentifier::new(
+                        child, source,
+                    )?));
                 }
                 "function_expression" => {
-                    exps.push(GroupByExpression::Func(FunctionExpression::new(child)));
+                    exps.push(GroupByExpression::Func(FunctionExpression::new(
+                        child, source,
+                    )?));
                 }
                 "having_clause" => {
-                    have_clause = Some(HavingClause::new(child));
+                    have_clause = Some(HavingClause::new(child, source)?);
                 }
-                other => {
-                    panic!("## unknown node: {} in GroupByClause", red(other));
+                _ => {
+                    return Err(unknown_node(child, "GroupByClause"));
                 }
             }
         }
-        Self {
+        Ok(Self {
             exps,
             have_clause,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5046,13 +5208,13 @@ pub struct HavingClause {
 }
 
 impl HavingClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "having_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "having_clause")?;
 
-        Self {
-            boolean_exp: BooleanExpression::new(node.first_c()),
+        Ok(Self {
+            boolean_exp: BooleanExpression::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5076,13 +5238,13 @@ pub struct SoslWithClause {
 }
 
 impl SoslWithClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "with_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "with_clause")?;
 
-        Self {
-            with_type: SoslWithType::new(node.c_by_k("with_type")),
+        Ok(Self {
+            with_type: SoslWithType::new(node.c_by_k("with_type")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5102,13 +5264,13 @@ pub struct SoqlWithClause {
 }
 
 impl SoqlWithClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "with_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "with_clause")?;
 
-        Self {
-            with_type: SoqlWithType::new(node.c_by_k("with_type")),
+        Ok(Self {
+            with_type: SoqlWithType::new(node.c_by_k("with_type")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5130,17 +5292,19 @@ pub enum SoqlWithTypeVariant {
 }
 
 impl SoqlWithTypeVariant {
-    pub fn new(node: Node) -> Self {
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
         let with_type = if node.named_child_count() == 0 {
-            return Self::SimpleType(ValueNode::new(node));
+            return Ok(Self::SimpleType(ValueNode::new(node, source)?));
         } else {
-            let child = node.first_c();
+            let child = node.first_c()?;
             match child.kind() {
-                "with_user_id_type" => Self::UserId(ValueNode::new(child.c_by_k("string_literal"))),
-                _ => panic_unknown_node(node, "WithType"),
+                "with_user_id_type" => {
+                    Self::UserId(ValueNode::new(child.c_by_k("string_literal")?, source)?)
+                }
+                _ => return Err(unknown_node(node, "WithType")),
             }
         };
-        with_type
+        Ok(with_type)
     }
 }
 
@@ -5171,23 +5335,29 @@ pub enum SoslWithType {
 }
 
 impl SoslWithType {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "with_type");
-
-        let child = node.first_c();
-        match child.kind() {
-            "with_data_cat_expression" => Self::DataCat(WithDataCatExpression::new(child)),
-            "with_division_expression" => Self::Division(WithDivisionExpression::new(child)),
-            "with_snippet_expression" => Self::Snippet(WithSnippetExpression::new(child)),
-            "with_network_expression" => Self::Network(WithNetworkExpression::new(child)),
-            "with_metadata_expression" => Self::Metadata(WithMetadataExpression::new(child)),
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "with_type")?;
+
+        let child = node.first_c()?;
+        Ok(match child.kind() {
+            "with_data_cat_expression" => Self::DataCat(WithDataCatExpression::new(child, source)?),
+            "with_division_expression" => {
+                Self::Division(WithDivisionExpression::new(child, source)?)
+            }
+            "with_snippet_expression" => Self::Snippet(WithSnippetExpression::new(child, source)?),
+            "with_network_expression" => Self::Network(WithNetworkExpression::new(child, source)?),
+            "with_metadata_expression" => {
+                Self::Metadata(WithMetadataExpression::new(child, source)?)
+            }
             "with_spell_correction_expression" => {
-                Self::Spell(WithSpellCorrectionExpression::new(child))
+                Self::Spell(WithSpellCorrectionExpression::new(child, source)?)
             }
             "with_highlight" => Self::Highlight,
-            "with_pricebook_expression" => Self::PriceBook(WithPriceBookExpression::new(child)),
-            _ => panic_unknown_node(child, "SoslWithType"),
-        }
+            "with_pricebook_expression" => {
+                Self::PriceBook(WithPriceBookExpression::new(child, source)?)
+            }
+            _ => return Err(unknown_node(child, "SoslWithType")),
+        })
     }
 }
 
@@ -5229,19 +5399,19 @@ pub struct WithDataCatExpression {
 }
 
 impl WithDataCatExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "with_data_cat_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "with_data_cat_expression")?;
 
         let filters = node
-            .cs_by_k("with_data_cat_filter")
+            .cs_by_k("with_data_cat_filter")?
             .into_iter()
-            .map(|n| WithDataCatFilter::new(n))
-            .collect();
+            .map(|n| WithDataCatFilter::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             filters,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5268,27 +5438,33 @@ pub struct WithDataCatFilter {
 }
 
 impl WithDataCatFilter {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "with_data_cat_filter");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "with_data_cat_filter")?;
 
-        let all_identififers = node.cs_by_k("identifier");
+        let all_identififers = node.cs_by_k("identifier")?;
         if all_identififers.len() < 2 {
-            panic!("At least 2 identifier nodes should exist in WithDataCatFilter");
+            return Err(EnrichError::new(
+                &node,
+                "expected at least 2 identifier nodes",
+            ));
         }
 
-        let identifier = ValueNode::new(all_identififers[0]);
+        let identifier = ValueNode::new(all_identififers[0], source)?;
         let identifiers: Vec<_> = all_identififers
             .into_iter()
             .skip(1)
-            .map(|n| ValueNode::new(n))
-            .collect();
+            .map(|n| ValueNode::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             identifier,
-            filter_type: ValueNodeUpperCase::new(node.c_by_k("with_data_cat_filter_type")),
+            filter_type: ValueNodeUpperCase::new(
+                node.c_by_k("with_data_cat_filter_type")?,
+                source,
+            )?,
             identifiers,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5322,15 +5498,15 @@ pub enum WithDivisionExpression {
 }
 
 impl WithDivisionExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "with_division_expression");
-
-        let child = node.first_c();
-        match child.kind() {
-            "bound_apex_expression" => Self::Bound(BoundApexExpression::new(child)),
-            "string_literal" => Self::StringLiteral(child.value()),
-            _ => panic_unknown_node(node, "WithDivisionExpression"),
-        }
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "with_division_expression")?;
+
+        let child = node.first_c()?;
+        Ok(match child.kind() {
+            "bound_apex_expression" => Self::Bound(BoundApexExpression::new(child, source)?),
+            "string_literal" => Self::StringLiteral(child.value(source)),
+            _ => return Err(unknown_node(node, "WithDivisionExpression")),
+        })
     }
 }
 
@@ -5355,15 +5531,18 @@ pub struct WithSnippetExpression {
 }
 
 impl WithSnippetExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "with_snippet_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "with_snippet_expression")?;
 
-        let int = node.try_c_by_k("int").map(|n| ValueNode::new(n));
+        let int = node
+            .try_c_by_k("int")
+            .map(|n| ValueNode::new(n, source))
+            .transpose()?;
 
-        Self {
+        Ok(Self {
             int,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5388,13 +5567,13 @@ pub struct WithNetworkExpression {
 }
 
 impl WithNetworkExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "with_network_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "with_network_expression")?;
 
-        Self {
-            comparison: get_comparsion(&node),
+        Ok(Self {
+            comparison: get_comparsion(&node, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5414,13 +5593,13 @@ pub struct WithMetadataExpression {
 }
 
 impl WithMetadataExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "with_metadata_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "with_metadata_expression")?;
 
-        Self {
-            string_literal: ValueNode::new(node.c_by_k("string_literal")),
+        Ok(Self {
+            string_literal: ValueNode::new(node.c_by_k("string_literal")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5440,13 +5619,13 @@ pub struct WithSpellCorrectionExpression {
 }
 
 impl WithSpellCorrectionExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "with_spell_correction_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "with_spell_correction_expression")?;
 
-        Self {
-            boolean: ValueNode::new(node.c_by_k("boolean")),
+        Ok(Self {
+            boolean: ValueNode::new(node.c_by_k("boolean")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5466,13 +5645,13 @@ pub struct WithPriceBookExpression {
 }
 
 impl WithPriceBookExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "with_pricebook_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "with_pricebook_expression")?;
 
-        Self {
-            string_literal: ValueNode::new(node.c_by_k("string_literal")),
+        Ok(Self {
+            string_literal: ValueNode::new(node.c_by_k("string_literal")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5492,19 +5671,19 @@ pub struct DottedIdentifier {
 }
 
 impl DottedIdentifier {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "dotted_identifier");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "dotted_identifier")?;
 
         let identifiers = node
-            .cs_by_k("identifier")
+            .cs_by_k("identifier")?
             .into_iter()
-            .map(|n| ValueNode::new(n))
-            .collect();
+            .map(|n| ValueNode::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             identifiers,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5528,11 +5707,11 @@ pub struct ValueNode {
 }
 
 impl ValueNode {
-    pub fn new(node: Node) -> Self {
-        Self {
-            value: node.value(),
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(Self {
+            value: node.value(source),
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5551,11 +5730,11 @@ pub struct ValueNodeLowerCase {
 }
 
 impl ValueNodeLowerCase {
-    pub fn new(node: Node) -> Self {
-        Self {
-            value: node.value(),
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(Self {
+            value: node.value(source),
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5574,11 +5753,11 @@ pub struct ValueNodeUpperCase {
 }
 
 impl ValueNodeUpperCase {
-    pub fn new(node: Node) -> Self {
-        Self {
-            value: node.value(),
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(Self {
+            value: node.value(source),
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5597,13 +5776,13 @@ pub struct ExpressionStatement {
 }
 
 impl ExpressionStatement {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "expression_statement");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "expression_statement")?;
 
-        Self {
-            exp: Expression::new(node.first_c()),
+        Ok(Self {
+            exp: Expression::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5622,12 +5801,12 @@ pub struct SafeNavigationOperator {
 }
 
 impl SafeNavigationOperator {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "safe_navigation_operator");
+    pub fn new(node: Node, _source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "safe_navigation_operator")?;
 
-        Self {
+        Ok(Self {
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5646,13 +5825,13 @@ pub struct CountExpression {
 }
 
 impl CountExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "count_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "count_expression")?;
 
-        Self {
-            function_name: ValueNode::new(node.c_by_n("function_name")),
+        Ok(Self {
+            function_name: ValueNode::new(node.c_by_n("function_name")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5672,13 +5851,13 @@ pub struct FunctionExpression {
 }
 
 impl FunctionExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "function_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "function_expression")?;
 
-        Self {
-            variant: FunctionExpressionVariant::new(node),
+        Ok(Self {
+            variant: FunctionExpressionVariant::new(node, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5697,13 +5876,13 @@ pub struct FieldIdentifier {
 }
 
 impl FieldIdentifier {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "field_identifier");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "field_identifier")?;
 
-        Self {
-            variant: FieldIdentifierVariant::new(node),
+        Ok(Self {
+            variant: FieldIdentifierVariant::new(node, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5722,13 +5901,13 @@ pub struct GeoLocationType {
 }
 
 impl GeoLocationType {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "geo_location_type");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "geo_location_type")?;
 
-        Self {
-            variant: GeoLocationTypeVariant::new(node),
+        Ok(Self {
+            variant: GeoLocationTypeVariant::new(node, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5747,13 +5926,13 @@ pub struct SelectClause {
 }
 
 impl SelectClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "select_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "select_clause")?;
 
-        Self {
-            variant: SelectClauseVariant::new(node),
+        Ok(Self {
+            variant: SelectClauseVariant::new(node, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5772,13 +5951,13 @@ pub struct StorageIdentifier {
 }
 
 impl StorageIdentifier {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "storage_identifier");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "storage_identifier")?;
 
-        Self {
-            variant: StorageIdentifierVariant::new(node),
+        Ok(Self {
+            variant: StorageIdentifierVariant::new(node, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5797,19 +5976,19 @@ pub struct AndExpression {
 }
 
 impl AndExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "and_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "and_expression")?;
 
         let condition_exps = node
             .children_vec()
             .into_iter()
-            .map(|n| ConditionExpression::new(n))
-            .collect();
+            .map(|n| ConditionExpression::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             condition_exps,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5834,19 +6013,19 @@ pub struct OrExpression {
 }
 
 impl OrExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "or_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "or_expression")?;
 
         let condition_exps = node
             .children_vec()
             .into_iter()
-            .map(|n| ConditionExpression::new(n))
-            .collect();
+            .map(|n| ConditionExpression::new(n, source))
+            .collect::<Result<_, _>>()?;
 
-        Self {
+        Ok(Self {
             condition_exps,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5871,13 +6050,13 @@ pub struct NotExpression {
 }
 
 impl NotExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "not_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "not_expression")?;
 
-        Self {
-            condition_exp: ConditionExpression::new(node.first_c()),
+        Ok(Self {
+            condition_exp: ConditionExpression::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5898,13 +6077,13 @@ pub struct SoqlWithType {
 }
 
 impl SoqlWithType {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "with_type");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "with_type")?;
 
-        Self {
-            variant: SoqlWithTypeVariant::new(node),
+        Ok(Self {
+            variant: SoqlWithTypeVariant::new(node, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5923,13 +6102,13 @@ pub struct ForClause {
 }
 
 impl ForClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "for_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "for_clause")?;
 
-        Self {
-            for_type: ValueNode::new(node.c_by_k("for_type")),
+        Ok(Self {
+            for_type: ValueNode::new(node.c_by_k("for_type")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5947,12 +6126,12 @@ pub struct AllRowsClause {
 }
 
 impl AllRowsClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "all_rows_clause");
+    pub fn new(node: Node, _source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "all_rows_clause")?;
 
-        Self {
+        Ok(Self {
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5971,13 +6150,13 @@ pub struct UpdateExpression {
 }
 
 impl UpdateExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "update_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "update_expression")?;
 
-        Self {
-            variant: UpdateExpressionVariant::new(node),
+        Ok(Self {
+            variant: UpdateExpressionVariant::new(node, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -5996,13 +6175,13 @@ pub struct DmlExpression {
 }
 
 impl DmlExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "dml_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "dml_expression")?;
 
-        Self {
-            variant: DmlExpressionVariant::new(node),
+        Ok(Self {
+            variant: DmlExpressionVariant::new(node, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -6021,13 +6200,13 @@ pub struct DmlType {
 }
 
 impl DmlType {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "dml_type");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "dml_type")?;
 
-        Self {
-            variant: DmlTypeVariant::new(node.first_c()),
+        Ok(Self {
+            variant: DmlTypeVariant::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
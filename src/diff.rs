@@ -0,0 +1,69 @@
+use crate::message_helper::{cyan, green, red};
+use similar::{ChangeTag, TextDiff};
+
+const CONTEXT_LINES: usize = 3;
+
+/// Renders a unified diff between the original and the formatted source of
+/// `path`. Returns an empty string when both are identical.
+pub fn unified_diff(path: &str, original: &str, formatted: &str, color: bool) -> String {
+    if original == formatted {
+        return String::new();
+    }
+
+    let diff = TextDiff::from_lines(original, formatted);
+    let mut out = String::new();
+
+    let paint = |p: Paint| color.then_some(p);
+
+    push_line(&mut out, &format!("--- {}", path), paint(red));
+    push_line(&mut out, &format!("+++ {}", path), paint(green));
+
+    for hunk in diff
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .iter_hunks()
+    {
+        push_line(&mut out, &hunk.header().to_string(), paint(cyan));
+
+        for change in hunk.iter_changes() {
+            let (sign, line_paint) = match change.tag() {
+                ChangeTag::Delete => ('-', paint(red)),
+                ChangeTag::Insert => ('+', paint(green)),
+                ChangeTag::Equal => (' ', None),
+            };
+            let line = format!("{}{}", sign, change.value().trim_end_matches(['\r', '\n']));
+            push_line(&mut out, &line, line_paint);
+
+            if change.missing_newline() {
+                out.push_str("\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    out
+}
+
+/// Number of lines removed from and added to `original` to get `formatted`.
+pub fn changed_line_counts(original: &str, formatted: &str) -> (usize, usize) {
+    TextDiff::from_lines(original, formatted)
+        .iter_all_changes()
+        .fold((0, 0), |(removed, added), change| match change.tag() {
+            ChangeTag::Delete => (removed + 1, added),
+            ChangeTag::Insert => (removed, added + 1),
+            ChangeTag::Equal => (removed, added),
+        })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type Paint = fn(&str) -> String;
+
+#[cfg(target_arch = "wasm32")]
+type Paint = fn(&str) -> &str;
+
+fn push_line(out: &mut String, line: &str, paint: Option<Paint>) {
+    match paint {
+        Some(paint) => out.push_str(&paint(line).to_string()),
+        None => out.push_str(line),
+    }
+    out.push('\n');
+}
@@ -1,8 +1,158 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
 pub type DocRef<'a> = &'a Doc<'a>;
 
-pub fn pretty_print(doc_ref: DocRef, max_width: u32) -> String {
-    let mut printer = PrettyPrinter::new(doc_ref, max_width);
-    printer.print()
+// with a `tab_width`, every `tab_width` columns of indentation are printed as a tab;
+// also returns where the output of every `Doc::MarkStart`/`Doc::MarkEnd` pair ended up
+pub fn pretty_print(
+    doc_ref: DocRef,
+    max_width: u32,
+    tab_width: Option<u32>,
+) -> (String, HashMap<usize, Range<usize>>) {
+    let mut printer = PrettyPrinter::new(doc_ref, max_width, tab_width);
+    let result = printer.print();
+    (result, printer.marks)
+}
+
+// prints `doc_ref` like `pretty_print` and returns, for every `Doc::Choice`
+// (by address), whether its flat branch was picked
+pub fn trace_choices(
+    doc_ref: DocRef,
+    max_width: u32,
+    tab_width: Option<u32>,
+) -> HashMap<usize, bool> {
+    let mut printer = PrettyPrinter::new(doc_ref, max_width, tab_width);
+    printer.choices = Some(HashMap::new());
+    printer.print();
+    printer.choices.unwrap_or_default()
+}
+
+// renders the doc tree one node per line, children indented below their parent;
+// `choices` from `trace_choices` annotates each `Choice` with the branch taken.
+// A subtree reached more than once, like `x` in `group(x)`, is printed once with
+// a `#n` label and referred to as `-> #n` everywhere else
+pub fn dump(doc_ref: DocRef, choices: Option<&HashMap<usize, bool>>) -> String {
+    let shared = shared_nodes(doc_ref);
+    let mut labels: HashMap<usize, usize> = HashMap::new();
+    let mut printed: HashSet<usize> = HashSet::new();
+    let mut label_of = |doc: DocRef| {
+        let next = labels.len() + 1;
+        *labels.entry(address(doc)).or_insert(next)
+    };
+
+    let mut result = String::new();
+    let mut stack = vec![(DumpItem::Doc(doc_ref), 0)];
+
+    while let Some((item, depth)) = stack.pop() {
+        result.push_str(&"  ".repeat(depth));
+        let doc = match item {
+            DumpItem::Doc(doc) => doc,
+            DumpItem::FlatOf(label) => {
+                result.push_str(&format!("Flat -> #{}\n", label));
+                continue;
+            }
+        };
+        if shared.contains(&address(doc)) && !printed.insert(address(doc)) {
+            result.push_str(&format!("-> #{}\n", label_of(doc)));
+            continue;
+        }
+
+        let mut children: Vec<DumpItem> = Vec::new();
+        match doc {
+            Doc::Newline => result.push_str("Newline"),
+            Doc::NewlineWithNoIndent => result.push_str("NewlineWithNoIndent"),
+            Doc::NewlineWhenInFlat => result.push_str("NewlineWhenInFlat"),
+            Doc::ForceBreak => result.push_str("ForceBreak"),
+            Doc::Text(text, _) => result.push_str(&format!("Text {:?}", text)),
+            Doc::Softline => result.push_str("Softline"),
+            Doc::Maybeline => result.push_str("Maybeline"),
+            Doc::Flat(x) => {
+                result.push_str("Flat");
+                children.push(DumpItem::Doc(x));
+            }
+            Doc::Indent(i, x) => {
+                result.push_str(&format!("Indent {}", i));
+                children.push(DumpItem::Doc(x));
+            }
+            Doc::Dedent(i, x) => {
+                result.push_str(&format!("Dedent {}", i));
+                children.push(DumpItem::Doc(x));
+            }
+            Doc::Concat(seq) => {
+                result.push_str("Concat");
+                children.extend(seq.iter().map(|x| DumpItem::Doc(x)));
+            }
+            Doc::Choice(x, y) => {
+                result.push_str("Choice");
+                let picked = choices.map(|c| c.get(&address(doc)));
+                match picked {
+                    Some(Some(true)) => result.push_str(" [flat]"),
+                    Some(Some(false)) => result.push_str(" [broken]"),
+                    Some(None) => result.push_str(" [not reached]"),
+                    None => {}
+                }
+                match x {
+                    // `group(y)`, the flat branch is the other one printed flat
+                    Doc::Flat(inner) if address(inner) == address(y) => {
+                        children.push(DumpItem::FlatOf(label_of(y)));
+                    }
+                    _ => children.push(DumpItem::Doc(x)),
+                }
+                children.push(DumpItem::Doc(y));
+            }
+            Doc::Align(offset, x) => {
+                result.push_str(&format!("Align {}", offset));
+                children.push(DumpItem::Doc(x));
+            }
+            Doc::MarkStart(id) => result.push_str(&format!("MarkStart {}", id)),
+            Doc::MarkEnd(id) => result.push_str(&format!("MarkEnd {}", id)),
+        };
+        if shared.contains(&address(doc)) {
+            result.push_str(&format!(" #{}", label_of(doc)));
+        }
+        result.push('\n');
+
+        for child in children.into_iter().rev() {
+            stack.push((child, depth + 1));
+        }
+    }
+
+    result
+}
+
+enum DumpItem<'a> {
+    Doc(DocRef<'a>),
+    // the flat branch of a `group`, labelled like its other branch
+    FlatOf(usize),
+}
+
+// addresses of the nodes with children that have more than one parent
+fn shared_nodes(doc_ref: DocRef) -> HashSet<usize> {
+    let mut seen = HashSet::new();
+    let mut shared = HashSet::new();
+    let mut stack = vec![doc_ref];
+
+    while let Some(doc) = stack.pop() {
+        let children: Vec<DocRef> = match doc {
+            Doc::Flat(x) | Doc::Indent(_, x) | Doc::Dedent(_, x) | Doc::Align(_, x) => vec![x],
+            Doc::Concat(seq) => seq.clone(),
+            Doc::Choice(x, y) => vec![x, y],
+            _ => continue,
+        };
+        if !seen.insert(address(doc)) {
+            shared.insert(address(doc));
+            continue;
+        }
+        stack.extend(children);
+    }
+
+    shared
+}
+
+// identifies a doc node, the same `Doc` value can appear more than once
+fn address(doc_ref: DocRef) -> usize {
+    doc_ref as *const Doc as usize
 }
 
 #[derive(PartialEq, Debug)]
@@ -19,25 +169,36 @@ pub enum Doc<'a> {
     Dedent(u32, DocRef<'a>),
     Concat(Vec<DocRef<'a>>),
     Choice(DocRef<'a>, DocRef<'a>),
-    //Align(u32, DocRef<'a>),
+    Align(u32, DocRef<'a>), // lines broken inside are aligned to the start column + offset
+    MarkStart(usize),       // zero width, records where the output of a node starts
+    MarkEnd(usize),
 }
 
 struct PrettyPrinter<'a> {
     max_width: u32,
+    tab_width: Option<u32>,
     col: u32,
     chunks: Vec<Chunk<'a>>,
+    marks: HashMap<usize, Range<usize>>,
+    // flat or not, per `Doc::Choice` address, only kept for `trace_choices`
+    choices: Option<HashMap<usize, bool>>,
 }
 
 pub struct PrettyConfig {
     pub indent_size: u32,
+    // align continuation lines (Visual) instead of indenting them (Block)
+    pub visual_indent: bool,
 }
 
 impl PrettyConfig {
-    pub fn new(indent_size: u32) -> Self {
+    pub fn new(indent_size: u32, visual_indent: bool) -> Self {
         if indent_size == 0 {
             panic!("indent_size must be greater than 0")
         } else {
-            Self { indent_size }
+            Self {
+                indent_size,
+                visual_indent,
+            }
         }
     }
 }
@@ -47,7 +208,7 @@ struct Chunk<'a> {
     doc_ref: DocRef<'a>,
     indent: u32,
     flat: bool,
-    //align: u32,
+    align: u32, // extra columns on top of `indent`, always printed as spaces
 }
 
 impl<'a> Chunk<'a> {
@@ -71,6 +232,14 @@ impl<'a> Chunk<'a> {
         }
     }
 
+    fn aligned(self, align: u32, doc_ref: DocRef<'a>) -> Self {
+        Chunk {
+            doc_ref,
+            align,
+            ..self
+        }
+    }
+
     fn flat(self, doc_ref: DocRef<'a>) -> Self {
         Chunk {
             doc_ref,
@@ -81,17 +250,21 @@ impl<'a> Chunk<'a> {
 }
 
 impl<'a> PrettyPrinter<'a> {
-    fn new(doc_ref: DocRef<'a>, max_width: u32) -> Self {
+    fn new(doc_ref: DocRef<'a>, max_width: u32, tab_width: Option<u32>) -> Self {
         let chunk = Chunk {
             doc_ref,
             indent: 0,
             flat: false,
+            align: 0,
         };
 
         Self {
             max_width,
+            tab_width,
             col: 0,
             chunks: vec![chunk],
+            marks: HashMap::new(),
+            choices: None,
         }
     }
 
@@ -138,23 +311,34 @@ impl<'a> PrettyPrinter<'a> {
                 Doc::Flat(x) => self.chunks.push(chunk.flat(x)),
                 Doc::Indent(i, x) => self.chunks.push(chunk.indented(*i, x)),
                 Doc::Dedent(i, x) => self.chunks.push(chunk.dedented(*i, x)),
+                Doc::MarkStart(id) => {
+                    self.marks.insert(*id, result.len()..result.len());
+                }
+                Doc::MarkEnd(id) => {
+                    if let Some(mark) = self.marks.get_mut(id) {
+                        mark.end = result.len();
+                    }
+                }
+                Doc::Align(offset, x) => {
+                    let align = (self.col + offset).saturating_sub(chunk.indent);
+                    self.chunks.push(chunk.aligned(align, x));
+                }
                 Doc::Concat(seq) => {
                     for n in seq.iter().rev() {
                         self.chunks.push(chunk.with_doc(n));
                     }
                 }
                 Doc::Choice(x, y) => {
-                    if chunk.flat {
-                        // 1. Already forced single-line by a parent
+                    // 1. Already forced single-line by a parent, or 2. let's see if x fits
+                    let flat = chunk.flat || self.fits(chunk.with_doc(x));
+                    if let Some(choices) = &mut self.choices {
+                        choices.insert(address(chunk.doc_ref), flat);
+                    }
+                    if flat {
                         self.chunks.push(chunk.with_doc(x));
                     } else {
-                        // 2. Let's see if x fits
-                        if self.fits(chunk.with_doc(x)) {
-                            self.chunks.push(chunk.with_doc(x));
-                        } else {
-                            // 3. x not fits, use the fall-back y (usually the multi-line version)
-                            self.chunks.push(chunk.with_doc(y));
-                        }
+                        // 3. x not fits, use the fall-back y (usually the multi-line version)
+                        self.chunks.push(chunk.with_doc(y));
                     }
                 }
             }
@@ -164,10 +348,19 @@ impl<'a> PrettyPrinter<'a> {
 
     fn insert_newline_with_indent(&mut self, result: &mut String, chunk: &Chunk) {
         result.push('\n');
-        let total_indent = chunk.indent;
-        for _ in 0..total_indent {
+        let total_indent = chunk.indent + chunk.align;
+        // alignment is always padded with spaces so it survives any tab width
+        let tabs = self
+            .tab_width
+            .map_or(0, |tab_width| chunk.indent / tab_width);
+        let spaces = total_indent - tabs * self.tab_width.unwrap_or(0);
+        for _ in 0..tabs {
+            result.push('\t');
+        }
+        for _ in 0..spaces {
             result.push(' ');
         }
+        // a tab counts as `tab_width` columns
         self.col = total_indent;
     }
 
@@ -194,6 +387,7 @@ impl<'a> PrettyPrinter<'a> {
                     }
                 }
                 Doc::ForceBreak => return false,
+                Doc::MarkStart(_) | Doc::MarkEnd(_) => {}
                 Doc::Softline => {
                     if chunk.flat {
                         if remaining_width >= 1 {
@@ -220,10 +414,8 @@ impl<'a> PrettyPrinter<'a> {
                 Doc::Flat(x) => stack.push(chunk.flat(x)),
                 Doc::Indent(i, x) => stack.push(chunk.indented(*i, x)),
                 Doc::Dedent(i, x) => stack.push(chunk.dedented(*i, x)),
-                //Doc::Align(relative_align_col, x) => {
-                //    let new_align = chunk.align + relative_align_col;
-                //    stack.push(chunk.align(new_align, x));
-                //}
+                // only the first line is measured, so alignment never matters here
+                Doc::Align(_, x) => stack.push(chunk.with_doc(x)),
                 Doc::Concat(seq) => {
                     for n in seq.iter().rev() {
                         stack.push(chunk.with_doc(n));
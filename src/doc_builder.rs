@@ -1,23 +1,68 @@
 use crate::{
+    context::{CommentBucket, CommentMap},
     data_model::DocBuild,
     doc::{Doc, DocRef, PrettyConfig},
     enum_def::BodyMember,
 };
+use std::collections::HashSet;
 use typed_arena::Arena;
+use unicode_width::UnicodeWidthStr;
 
 pub struct DocBuilder<'a> {
     arena: Arena<Doc<'a>>,
     config: PrettyConfig,
+    comment_map: CommentMap,
+    source_code: &'a str,
+    // nodes whose output position is recorded while printing
+    marked: HashSet<usize>,
 }
 
 impl<'a> DocBuilder<'a> {
-    pub fn new(config: PrettyConfig) -> Self {
+    pub fn new(config: PrettyConfig, comment_map: CommentMap, source_code: &'a str) -> Self {
         Self {
             arena: Arena::new(),
             config,
+            comment_map,
+            source_code,
+            marked: HashSet::new(),
         }
     }
 
+    pub fn with_marked(mut self, node_ids: impl IntoIterator<Item = usize>) -> Self {
+        self.marked.extend(node_ids);
+        self
+    }
+
+    pub fn is_marked(&self, node_id: &usize) -> bool {
+        self.marked.contains(node_id)
+    }
+
+    // wraps the output of a node so its position can be found in the printed text
+    pub fn mark(&'a self, node_id: usize, doc_ref: DocRef<'a>) -> DocRef<'a> {
+        if !self.is_marked(&node_id) {
+            return doc_ref;
+        }
+        self.concat(vec![
+            self.arena.alloc(Doc::MarkStart(node_id)),
+            doc_ref,
+            self.arena.alloc(Doc::MarkEnd(node_id)),
+        ])
+    }
+
+    pub fn comment_map(&self) -> &CommentMap {
+        &self.comment_map
+    }
+
+    pub fn source_code(&self) -> &'a str {
+        self.source_code
+    }
+
+    pub fn comment_bucket(&self, node_id: &usize) -> &CommentBucket {
+        self.comment_map
+            .get(node_id)
+            .unwrap_or_else(|| panic!("## comment_map missing bucket for node: {}", node_id))
+    }
+
     // NOTE: group does NOT work with b.nl() so don't wrap b.nl() in any inputs
     pub fn group_surround(
         &'a self,
@@ -163,7 +208,7 @@ impl<'a> DocBuilder<'a> {
             member_docs.push(m.member.build(self));
 
             if i < members.len() - 1 {
-                if m.has_trailing_newline {
+                if m.has_trailing_newline(self) {
                     member_docs.push(self.nl_with_no_indent());
                 }
                 member_docs.push(self.nl());
@@ -224,7 +269,8 @@ impl<'a> DocBuilder<'a> {
 
     pub fn txt(&'a self, text: impl ToString) -> DocRef<'a> {
         let s = text.to_string();
-        let width = s.len() as u32;
+        // display columns, not bytes: wide chars count 2, combining marks 0
+        let width = s.width() as u32;
         self.arena.alloc(Doc::Text(s, width))
     }
 
@@ -246,6 +292,18 @@ impl<'a> DocBuilder<'a> {
         self.txt(space_s_space)
     }
 
+    // original source printed as-is, lines after the first keep their own indentation
+    pub fn verbatim(&'a self, source: &str) -> DocRef<'a> {
+        let mut docs = Vec::new();
+        for (i, line) in source.split('\n').enumerate() {
+            if i > 0 {
+                docs.push(self.nl_with_no_indent());
+            }
+            docs.push(self.txt(line));
+        }
+        self.concat(docs)
+    }
+
     pub fn flat(&'a self, doc_ref: DocRef<'a>) -> DocRef<'a> {
         self.arena.alloc(Doc::Flat(doc_ref))
     }
@@ -260,9 +318,13 @@ impl<'a> DocBuilder<'a> {
         self.arena.alloc(Doc::Dedent(relative_indent, doc_ref))
     }
 
-    //fn align(&'a self, relative_col_offset: u32, doc_ref: DocRef<'a>) -> DocRef<'a> {
-    //    self.arena.alloc(Doc::Align(relative_col_offset, doc_ref))
-    //}
+    pub fn align(&'a self, relative_col_offset: u32, doc_ref: DocRef<'a>) -> DocRef<'a> {
+        self.arena.alloc(Doc::Align(relative_col_offset, doc_ref))
+    }
+
+    pub fn visual_indent(&self) -> bool {
+        self.config.visual_indent
+    }
 
     pub fn concat(&'a self, doc_refs: impl IntoIterator<Item = DocRef<'a>>) -> DocRef<'a> {
         let n_vec = doc_refs.into_iter().collect::<Vec<_>>();
@@ -4,7 +4,8 @@ use crate::{
     data_model::*,
     doc::DocRef,
     doc_builder::{DocBuilder, Insertable},
-    utility::{assert_check, build_with_comments, get_comment_bucket, panic_unknown_node},
+    error::EnrichError,
+    utility::{assert_check, build_with_comments, unknown_node},
 };
 use tree_sitter::Node;
 
@@ -17,14 +18,16 @@ pub enum RootMember {
 }
 
 impl RootMember {
-    pub fn new(n: Node) -> Self {
-        match n.kind() {
-            "class_declaration" => Self::Class(Box::new(ClassDeclaration::new(n))),
-            "enum_declaration" => Self::Enum(Box::new(EnumDeclaration::new(n))),
-            "trigger_declaration" => Self::Trigger(Box::new(TriggerDeclaration::new(n))),
-            "interface_declaration" => Self::Interface(Box::new(InterfaceDeclaration::new(n))),
-            _ => panic_unknown_node(n, "Root"),
-        }
+    pub fn new(n: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match n.kind() {
+            "class_declaration" => Self::Class(Box::new(ClassDeclaration::new(n, source)?)),
+            "enum_declaration" => Self::Enum(Box::new(EnumDeclaration::new(n, source)?)),
+            "trigger_declaration" => Self::Trigger(Box::new(TriggerDeclaration::new(n, source)?)),
+            "interface_declaration" => {
+                Self::Interface(Box::new(InterfaceDeclaration::new(n, source)?))
+            }
+            _ => return Err(unknown_node(n, "Root")),
+        })
     }
 }
 
@@ -61,18 +64,20 @@ pub enum ClassMember {
 }
 
 impl ClassMember {
-    pub fn new(n: Node) -> Self {
-        match n.kind() {
-            "field_declaration" => Self::Field(Box::new(FieldDeclaration::new(n))),
-            "class_declaration" => Self::NestedClass(Box::new(ClassDeclaration::new(n))),
-            "method_declaration" => Self::Method(Box::new(MethodDeclaration::new(n))),
-            "interface_declaration" => Self::Interface(Box::new(InterfaceDeclaration::new(n))),
-            "block" => Self::Block(Box::new(Block::new(n))),
-            "constructor_declaration" => Self::Constructor(ConstructorDeclaration::new(n)),
-            "enum_declaration" => Self::Enum(EnumDeclaration::new(n)),
-            "static_initializer" => Self::Static(StaticInitializer::new(n)),
-            _ => panic_unknown_node(n, "ClassMember"),
-        }
+    pub fn new(n: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match n.kind() {
+            "field_declaration" => Self::Field(Box::new(FieldDeclaration::new(n, source)?)),
+            "class_declaration" => Self::NestedClass(Box::new(ClassDeclaration::new(n, source)?)),
+            "method_declaration" => Self::Method(Box::new(MethodDeclaration::new(n, source)?)),
+            "interface_declaration" => {
+                Self::Interface(Box::new(InterfaceDeclaration::new(n, source)?))
+            }
+            "block" => Self::Block(Box::new(Block::new(n, source)?)),
+            "constructor_declaration" => Self::Constructor(ConstructorDeclaration::new(n, source)?),
+            "enum_declaration" => Self::Enum(EnumDeclaration::new(n, source)?),
+            "static_initializer" => Self::Static(StaticInitializer::new(n, source)?),
+            _ => return Err(unknown_node(n, "ClassMember")),
+        })
     }
 }
 
@@ -114,17 +119,17 @@ pub enum UnannotatedType {
 }
 
 impl UnannotatedType {
-    pub fn new(n: Node) -> Self {
-        match n.kind() {
+    pub fn new(n: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match n.kind() {
             "type_identifier"
             | "void_type"
             | "boolean_type"
             | "generic_type"
             | "java_type"
-            | "scoped_type_identifier" => Self::Simple(SimpleType::new(n)),
-            "array_type" => Self::Array(Box::new(ArrayType::new(n))),
-            _ => panic_unknown_node(n, "UnnanotatedType"),
-        }
+            | "scoped_type_identifier" => Self::Simple(SimpleType::new(n, source)?),
+            "array_type" => Self::Array(Box::new(ArrayType::new(n, source)?)),
+            _ => return Err(unknown_node(n, "UnnanotatedType")),
+        })
     }
 }
 
@@ -148,16 +153,16 @@ pub enum SimpleType {
 }
 
 impl SimpleType {
-    pub fn new(n: Node) -> Self {
-        match n.kind() {
-            "type_identifier" => Self::Identifier(ValueNode::new(n)),
-            "void_type" => Self::Void(ValueNode::new(n)),
-            "boolean_type" => Self::Bool(BoolType::new(n)),
-            "java_type" => Self::Java(JavaType::new(n)),
-            "generic_type" => Self::Generic(GenericType::new(n)),
-            "scoped_type_identifier" => Self::Scoped(ScopedTypeIdentifier::new(n)),
-            _ => panic_unknown_node(n, "SimpleType"),
-        }
+    pub fn new(n: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match n.kind() {
+            "type_identifier" => Self::Identifier(ValueNode::new(n, source)?),
+            "void_type" => Self::Void(ValueNode::new(n, source)?),
+            "boolean_type" => Self::Bool(BoolType::new(n, source)?),
+            "java_type" => Self::Java(JavaType::new(n, source)?),
+            "generic_type" => Self::Generic(GenericType::new(n, source)?),
+            "scoped_type_identifier" => Self::Scoped(ScopedTypeIdentifier::new(n, source)?),
+            _ => return Err(unknown_node(n, "SimpleType")),
+        })
     }
 }
 
@@ -193,11 +198,11 @@ pub enum VariableInitializer {
 }
 
 impl VariableInitializer {
-    pub fn new(n: Node) -> Self {
-        match n.kind() {
-            "array_initializer" => Self::Array(Box::new(ArrayInitializer::new(n))),
-            _ => Self::Exp(Expression::new(n)),
-        }
+    pub fn new(n: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match n.kind() {
+            "array_initializer" => Self::Array(Box::new(ArrayInitializer::new(n, source)?)),
+            _ => Self::Exp(Expression::new(n, source)?),
+        })
     }
 }
 
@@ -228,10 +233,12 @@ pub enum Expression {
 }
 
 impl Expression {
-    pub fn new(n: Node) -> Self {
-        match n.kind() {
-            "assignment_expression" => Self::Assignment(Box::new(AssignmentExpression::new(n))),
-            "binary_expression" => Self::Binary(Box::new(BinaryExpression::new(n))),
+    pub fn new(n: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match n.kind() {
+            "assignment_expression" => {
+                Self::Assignment(Box::new(AssignmentExpression::new(n, source)?))
+            }
+            "binary_expression" => Self::Binary(Box::new(BinaryExpression::new(n, source)?)),
             "int"
             | "decimal_floating_point_literal"
             | "query_expression"
@@ -249,15 +256,19 @@ impl Expression {
             | "version_expression"
             | "java_field_access"
             | "this"
-            | "array_creation_expression" => Self::Primary(Box::new(PrimaryExpression::new(n))),
-            "update_expression" => Self::Update(UpdateExpression::new(n)),
-            "unary_expression" => Self::Unary(UnaryExpression::new(n)),
-            "dml_expression" => Self::Dml(Box::new(DmlExpression::new(n))),
-            "ternary_expression" => Self::Te(Box::new(TernaryExpression::new(n))),
-            "cast_expression" => Self::Cast(Box::new(CastExpression::new(n))),
-            "instanceof_expression" => Self::Instance(Box::new(InstanceOfExpression::new(n))),
-            _ => panic_unknown_node(n, "Expression"),
-        }
+            | "array_creation_expression" => {
+                Self::Primary(Box::new(PrimaryExpression::new(n, source)?))
+            }
+            "update_expression" => Self::Update(UpdateExpression::new(n, source)?),
+            "unary_expression" => Self::Unary(UnaryExpression::new(n, source)?),
+            "dml_expression" => Self::Dml(Box::new(DmlExpression::new(n, source)?)),
+            "ternary_expression" => Self::Te(Box::new(TernaryExpression::new(n, source)?)),
+            "cast_expression" => Self::Cast(Box::new(CastExpression::new(n, source)?)),
+            "instanceof_expression" => {
+                Self::Instance(Box::new(InstanceOfExpression::new(n, source)?))
+            }
+            _ => return Err(unknown_node(n, "Expression")),
+        })
     }
 }
 
@@ -332,28 +343,30 @@ pub enum PrimaryExpression {
 }
 
 impl PrimaryExpression {
-    pub fn new(n: Node) -> Self {
-        match n.kind() {
+    pub fn new(n: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match n.kind() {
             "int"
             | "decimal_floating_point_literal"
             | "boolean"
             | "null_literal"
-            | "string_literal" => Self::Literal(Literal_::new(n)),
-            "identifier" => Self::Identifier(ValueNode::new(n)),
-            "class_literal" => Self::Class(ClassLiteral::new(n)),
-            "method_invocation" => Self::Method(MethodInvocation::new(n)),
-            "parenthesized_expression" => Self::Parenth(ParenthesizedExpression::new(n)),
-            "object_creation_expression" => Self::Obj(ObjectCreationExpression::new(n)),
-            "map_creation_expression" => Self::Map(MapCreationExpression::new(n)),
-            "field_access" => Self::Field(FieldAccess::new(n)),
-            "array_access" => Self::Array(Box::new(ArrayAccess::new(n))),
-            "array_creation_expression" => Self::ArrayCreation(ArrayCreationExpression::new(n)),
-            "version_expression" => Self::Version(VersionExpression::new(n)),
-            "query_expression" => Self::Query(QueryExpression::new(n)),
-            "java_field_access" => Self::Java(JavaFieldAccess::new(n)),
-            "this" => Self::This(This::new(n)),
-            _ => panic_unknown_node(n, "PrimaryExpression"),
-        }
+            | "string_literal" => Self::Literal(Literal_::new(n, source)?),
+            "identifier" => Self::Identifier(ValueNode::new(n, source)?),
+            "class_literal" => Self::Class(ClassLiteral::new(n, source)?),
+            "method_invocation" => Self::Method(MethodInvocation::new(n, source)?),
+            "parenthesized_expression" => Self::Parenth(ParenthesizedExpression::new(n, source)?),
+            "object_creation_expression" => Self::Obj(ObjectCreationExpression::new(n, source)?),
+            "map_creation_expression" => Self::Map(MapCreationExpression::new(n, source)?),
+            "field_access" => Self::Field(FieldAccess::new(n, source)?),
+            "array_access" => Self::Array(Box::new(ArrayAccess::new(n, source)?)),
+            "array_creation_expression" => {
+                Self::ArrayCreation(ArrayCreationExpression::new(n, source)?)
+            }
+            "version_expression" => Self::Version(VersionExpression::new(n, source)?),
+            "query_expression" => Self::Query(QueryExpression::new(n, source)?),
+            "java_field_access" => Self::Java(JavaFieldAccess::new(n, source)?),
+            "this" => Self::This(This::new(n, source)?),
+            _ => return Err(unknown_node(n, "PrimaryExpression")),
+        })
     }
 }
 
@@ -413,13 +426,13 @@ pub struct ClassLiteral {
 }
 
 impl ClassLiteral {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "class_literal");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "class_literal")?;
 
-        Self {
-            type_: UnannotatedType::new(node.first_c()),
+        Ok(Self {
+            type_: UnannotatedType::new(node.first_c()?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -442,15 +455,17 @@ pub enum Literal_ {
 }
 
 impl Literal_ {
-    pub fn new(node: Node) -> Self {
-        match node.kind() {
-            "boolean" => Self::Bool(ValueNodeLowerCase::new(node)),
-            "null_literal" => Self::Null(ValueNodeLowerCase::new(node)),
-            "int" => Self::Int(ValueNode::new(node)),
-            "string_literal" => Self::Str(ValueNode::new(node)),
-            "decimal_floating_point_literal" => Self::Decimal(ValueNodeLowerCase::new(node)),
-            _ => panic_unknown_node(node, "Literal_"),
-        }
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match node.kind() {
+            "boolean" => Self::Bool(ValueNodeLowerCase::new(node, source)?),
+            "null_literal" => Self::Null(ValueNodeLowerCase::new(node, source)?),
+            "int" => Self::Int(ValueNode::new(node, source)?),
+            "string_literal" => Self::Str(ValueNode::new(node, source)?),
+            "decimal_floating_point_literal" => {
+                Self::Decimal(ValueNodeLowerCase::new(node, source)?)
+            }
+            _ => return Err(unknown_node(node, "Literal_")),
+        })
     }
 }
 
@@ -496,9 +511,9 @@ pub enum ModifierKind {
 }
 
 impl ModifierKind {
-    pub fn new(n: Node) -> Self {
+    pub fn new(n: Node, _source: &str) -> Result<Self, EnrichError> {
         let kind = n.kind();
-        match kind {
+        Ok(match kind {
             "global" => Self::Global,
             "public" => Self::Public,
             "with_sharing" => Self::WithSharing,
@@ -514,8 +529,8 @@ impl ModifierKind {
             "testMethod" => Self::TestMethod,
             "transient" => Self::Transient,
             "webservice" => Self::Webservice,
-            _ => panic_unknown_node(n, "Modifier"),
-        }
+            _ => return Err(unknown_node(n, "Modifier")),
+        })
     }
 }
 
@@ -612,26 +627,28 @@ pub enum Statement {
 }
 
 impl Statement {
-    pub fn new(n: Node) -> Self {
-        match n.kind() {
-            "if_statement" => Self::If(Box::new(IfStatement::new(n))),
-            "expression_statement" => Self::Exp(ExpressionStatement::new(n)),
-            "local_variable_declaration" => Self::Local(LocalVariableDeclaration::new(n)),
-            "block" => Self::Block(Box::new(Block::new(n))),
-            "for_statement" => Self::For(Box::new(ForStatement::new(n))),
-            "enhanced_for_statement" => Self::EnhancedFor(Box::new(EnhancedForStatement::new(n))),
-            "run_as_statement" => Self::Run(RunAsStatement::new(n)),
-            "do_statement" => Self::Do(Box::new(DoStatement::new(n))),
-            "while_statement" => Self::While(Box::new(WhileStatement::new(n))),
-            "return_statement" => Self::Return(ReturnStatement::new(n)),
-            "try_statement" => Self::Try(Box::new(TryStatement::new(n))),
-            "throw_statement" => Self::Throw(ThrowStatement::new(n)),
-            "break_statement" => Self::Break(BreakStatement::new(n)),
-            "continue_statement" => Self::Continue(ContinueStatement::new(n)),
-            "switch_expression" => Self::Switch(Box::new(SwitchExpression::new(n))),
+    pub fn new(n: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match n.kind() {
+            "if_statement" => Self::If(Box::new(IfStatement::new(n, source)?)),
+            "expression_statement" => Self::Exp(ExpressionStatement::new(n, source)?),
+            "local_variable_declaration" => Self::Local(LocalVariableDeclaration::new(n, source)?),
+            "block" => Self::Block(Box::new(Block::new(n, source)?)),
+            "for_statement" => Self::For(Box::new(ForStatement::new(n, source)?)),
+            "enhanced_for_statement" => {
+                Self::EnhancedFor(Box::new(EnhancedForStatement::new(n, source)?))
+            }
+            "run_as_statement" => Self::Run(RunAsStatement::new(n, source)?),
+            "do_statement" => Self::Do(Box::new(DoStatement::new(n, source)?)),
+            "while_statement" => Self::While(Box::new(WhileStatement::new(n, source)?)),
+            "return_statement" => Self::Return(ReturnStatement::new(n, source)?),
+            "try_statement" => Self::Try(Box::new(TryStatement::new(n, source)?)),
+            "throw_statement" => Self::Throw(ThrowStatement::new(n, source)?),
+            "break_statement" => Self::Break(BreakStatement::new(n, source)?),
+            "continue_statement" => Self::Continue(ContinueStatement::new(n, source)?),
+            "switch_expression" => Self::Switch(Box::new(SwitchExpression::new(n, source)?)),
             ";" => Self::SemiColumn,
-            _ => panic_unknown_node(n, "Statement"),
-        }
+            _ => return Err(unknown_node(n, "Statement")),
+        })
     }
     pub fn is_block(&self) -> bool {
         matches!(self, Statement::Block(_))
@@ -699,17 +716,21 @@ pub enum Type {
 }
 
 impl Type {
-    pub fn new(n: Node) -> Self {
-        match n.kind() {
+    pub fn new(n: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match n.kind() {
             "type_identifier"
             | "void_type"
             | "boolean_type"
             | "generic_type"
             | "scoped_type_identifier"
-            | "java_type" => Self::Unannotated(UnannotatedType::Simple(SimpleType::new(n))),
-            "array_type" => Self::Unannotated(UnannotatedType::Array(Box::new(ArrayType::new(n)))),
-            _ => panic_unknown_node(n, "Type"),
-        }
+            | "java_type" => {
+                Self::Unannotated(UnannotatedType::Simple(SimpleType::new(n, source)?))
+            }
+            "array_type" => {
+                Self::Unannotated(UnannotatedType::Array(Box::new(ArrayType::new(n, source)?)))
+            }
+            _ => return Err(unknown_node(n, "Type")),
+        })
     }
 }
 
@@ -750,22 +771,22 @@ pub enum AnnotationArgumentList {
 }
 
 impl AnnotationArgumentList {
-    pub fn new(n: Node) -> Self {
+    pub fn new(n: Node, source: &str) -> Result<Self, EnrichError> {
         if n.named_child_count() == 0 {
-            return Self::Nil;
+            return Ok(Self::Nil);
         }
 
         let key_values = n.try_cs_by_k("annotation_key_value");
 
-        if key_values.is_empty() {
-            Self::Value(ValueNode::new(n.c_by_n("value")))
+        Ok(if key_values.is_empty() {
+            Self::Value(ValueNode::new(n.c_by_n("value")?, source)?)
         } else {
             let key_values = key_values
                 .into_iter()
-                .map(AnnotationKeyValue::new)
-                .collect();
+                .map(|n| AnnotationKeyValue::new(n, source))
+                .collect::<Result<_, _>>()?;
             Self::KeyValues(key_values)
-        }
+        })
     }
 }
 
@@ -796,21 +817,26 @@ impl<'a> DocBuild<'a> for AnnotationArgumentList {
 #[derive(Debug)]
 pub struct BodyMember<M> {
     pub member: M,
-    pub has_trailing_newline: bool, // already take comment nodes into consideration
+    pub has_newline_below: bool, // comment nodes not taken into consideration
+    pub node_info: NodeInfo,
 }
 
 impl<M> BodyMember<M> {
     pub fn new(node: &Node, member: M) -> Self {
+        let has_newline_below = node.next_named_sibling().map_or(false, |n| {
+            node.end_position().row < n.start_position().row - 1
+        });
+
         Self {
             member,
-            has_trailing_newline: Self::has_trailing_newline(node),
+            has_newline_below,
+            node_info: NodeInfo::from(node),
         }
     }
 
     // take comment nodes into consideration
-    fn has_trailing_newline(node: &Node) -> bool {
-        let node_id = &node.id();
-        let bucket = get_comment_bucket(node_id);
+    pub fn has_trailing_newline(&self, b: &DocBuilder) -> bool {
+        let bucket = b.comment_bucket(&self.node_info.id);
 
         // we assume post_comments are always inline
         // because this method is not called by last element of BodyMember
@@ -818,9 +844,7 @@ impl<M> BodyMember<M> {
             return last_post_comment.has_newline_below();
         }
 
-        node.next_named_sibling().map_or(false, |n| {
-            node.end_position().row < n.start_position().row - 1
-        })
+        self.has_newline_below
     }
 }
 
@@ -836,8 +860,8 @@ pub enum TriggerEventVariant {
 }
 
 impl TriggerEventVariant {
-    pub fn new(n: Node) -> Self {
-        match n.kind() {
+    pub fn new(n: Node, _source: &str) -> Result<Self, EnrichError> {
+        Ok(match n.kind() {
             "before_insert" => Self::BeforeInsert,
             "before_update" => Self::BeforeUpdate,
             "before_delete" => Self::BeforeDelete,
@@ -845,8 +869,8 @@ impl TriggerEventVariant {
             "after_update" => Self::AfterUpdate,
             "after_delete" => Self::AfterDelete,
             "after_undelete" => Self::AfterUndelete,
-            _ => panic_unknown_node(n, "TriggerEvent"),
-        }
+            _ => return Err(unknown_node(n, "TriggerEvent")),
+        })
     }
 }
 
@@ -885,19 +909,21 @@ pub enum SelectClauseVariant {
 }
 
 impl SelectClauseVariant {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "select_clause");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "select_clause")?;
 
-        if let Some(count_node) = node.try_c_by_k("count_expression") {
-            Self::Count(CountExpression::new(count_node))
-        } else {
-            Self::Selectable(
-                node.children_vec()
-                    .into_iter()
-                    .map(|n| SelectableExpression::new(n))
-                    .collect(),
-            )
-        }
+        Ok(
+            if let Some(count_node) = node.try_c_by_k("count_expression") {
+                Self::Count(CountExpression::new(count_node, source)?)
+            } else {
+                Self::Selectable(
+                    node.children_vec()
+                        .into_iter()
+                        .map(|n| SelectableExpression::new(n, source))
+                        .collect::<Result<_, _>>()?,
+                )
+            },
+        )
     }
 }
 
@@ -934,17 +960,19 @@ pub enum SelectableExpression {
 }
 
 impl SelectableExpression {
-    pub fn new(node: Node) -> Self {
-        match node.kind() {
-            "field_identifier" => Self::Value(ValueExpression::Field(FieldIdentifier::new(node))),
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match node.kind() {
+            "field_identifier" => {
+                Self::Value(ValueExpression::Field(FieldIdentifier::new(node, source)?))
+            }
             "function_expression" => Self::Value(ValueExpression::Function(Box::new(
-                FunctionExpression::new(node),
+                FunctionExpression::new(node, source)?,
             ))),
-            "alias_expression" => Self::Alias(AliasExpression::new(node)),
-            "fields_expression" => Self::Fields(FieldsExpression::new(node)),
-            "subquery" => Self::Sub(SubQuery::new(node)),
-            _ => panic_unknown_node(node, "SelectableExpression"),
-        }
+            "alias_expression" => Self::Alias(AliasExpression::new(node, source)?),
+            "fields_expression" => Self::Fields(FieldsExpression::new(node, source)?),
+            "subquery" => Self::Sub(SubQuery::new(node, source)?),
+            _ => return Err(unknown_node(node, "SelectableExpression")),
+        })
     }
 }
 
@@ -974,13 +1002,13 @@ pub struct FieldsExpression {
 }
 
 impl FieldsExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "fields_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "fields_expression")?;
 
-        Self {
-            fields_type: ValueNodeUpperCase::new(node.c_by_k("fields_type")),
+        Ok(Self {
+            fields_type: ValueNodeUpperCase::new(node.c_by_k("fields_type")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1002,14 +1030,14 @@ pub struct AliasExpression {
 }
 
 impl AliasExpression {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "alias_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "alias_expression")?;
 
-        Self {
-            value_exp: ValueExpression::new(node.first_c()),
-            identifier: ValueNode::new(node.c_by_k("identifier")),
+        Ok(Self {
+            value_exp: ValueExpression::new(node.first_c()?, source)?,
+            identifier: ValueNode::new(node.c_by_k("identifier")?, source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1030,15 +1058,15 @@ pub enum FieldIdentifierVariant {
 }
 
 impl FieldIdentifierVariant {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "field_identifier");
-
-        let c = node.first_c();
-        match c.kind() {
-            "identifier" => Self::Identifier(ValueNode::new(c)),
-            "dotted_identifier" => Self::Dotted(DottedIdentifier::new(c)),
-            _ => panic_unknown_node(c, "FieldIdentifier"),
-        }
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "field_identifier")?;
+
+        let c = node.first_c()?;
+        Ok(match c.kind() {
+            "identifier" => Self::Identifier(ValueNode::new(c, source)?),
+            "dotted_identifier" => Self::Dotted(DottedIdentifier::new(c, source)?),
+            _ => return Err(unknown_node(c, "FieldIdentifier")),
+        })
     }
 }
 
@@ -1062,12 +1090,12 @@ pub enum StorageVariant {
 }
 
 impl StorageVariant {
-    pub fn new(node: Node) -> StorageVariant {
-        match node.kind() {
-            "storage_alias" => Self::Alias(StorageAlias::new(node)),
-            "storage_identifier" => Self::Identifier(StorageIdentifier::new(node)),
-            _ => panic_unknown_node(node, "StorageVariant"),
-        }
+    pub fn new(node: Node, source: &str) -> Result<StorageVariant, EnrichError> {
+        Ok(match node.kind() {
+            "storage_alias" => Self::Alias(StorageAlias::new(node, source)?),
+            "storage_identifier" => Self::Identifier(StorageIdentifier::new(node, source)?),
+            _ => return Err(unknown_node(node, "StorageVariant")),
+        })
     }
 }
 
@@ -1091,20 +1119,20 @@ pub enum StorageIdentifierVariant {
 }
 
 impl StorageIdentifierVariant {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "storage_identifier");
-        let c = node.first_c();
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "storage_identifier")?;
+        let c = node.first_c()?;
 
-        match c.kind() {
-            "identifier" => Self::Identifier(ValueNode::new(c)),
+        Ok(match c.kind() {
+            "identifier" => Self::Identifier(ValueNode::new(c, source)?),
             "dotted_identifier" => Self::Dotted(
-                c.cs_by_k("identifier")
+                c.cs_by_k("identifier")?
                     .into_iter()
-                    .map(|n| ValueNode::new(n))
-                    .collect(),
+                    .map(|n| ValueNode::new(n, source))
+                    .collect::<Result<_, _>>()?,
             ),
-            _ => panic_unknown_node(c, "StorageIdentifier"),
-        }
+            _ => return Err(unknown_node(c, "StorageIdentifier")),
+        })
     }
 }
 
@@ -1131,12 +1159,12 @@ pub enum LimitValue {
 }
 
 impl LimitValue {
-    pub fn new(n: Node) -> Self {
-        match n.kind() {
-            "int" => Self::Int(ValueNode::new(n)),
-            "bound_apex_expression" => Self::Bound(BoundApexExpression::new(n)),
-            _ => panic_unknown_node(n, "LimitValue"),
-        }
+    pub fn new(n: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match n.kind() {
+            "int" => Self::Int(ValueNode::new(n, source)?),
+            "bound_apex_expression" => Self::Bound(BoundApexExpression::new(n, source)?),
+            _ => return Err(unknown_node(n, "LimitValue")),
+        })
     }
 }
 
@@ -1162,13 +1190,13 @@ pub enum BooleanExpression {
 }
 
 impl BooleanExpression {
-    pub fn new(node: Node) -> Self {
-        match node.kind() {
-            "and_expression" => Self::And(AndExpression::new(node)),
-            "or_expression" => Self::Or(OrExpression::new(node)),
-            "not_expression" => Self::Not(NotExpression::new(node)),
-            _ => Self::Condition(Box::new(ConditionExpression::new(node))),
-        }
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match node.kind() {
+            "and_expression" => Self::And(AndExpression::new(node, source)?),
+            "or_expression" => Self::Or(OrExpression::new(node, source)?),
+            "not_expression" => Self::Not(NotExpression::new(node, source)?),
+            _ => Self::Condition(Box::new(ConditionExpression::new(node, source)?)),
+        })
     }
 
     fn operator(&self) -> Option<&str> {
@@ -1208,11 +1236,11 @@ pub enum ConditionExpression {
 }
 
 impl ConditionExpression {
-    pub fn new(node: Node) -> Self {
-        match node.kind() {
-            "comparison_expression" => Self::Comparison(ComparisonExpression::new(node)),
-            _ => Self::Bool(Box::new(BooleanExpression::new(node))),
-        }
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match node.kind() {
+            "comparison_expression" => Self::Comparison(ComparisonExpression::new(node, source)?),
+            _ => Self::Bool(Box::new(BooleanExpression::new(node, source)?)),
+        })
     }
 
     pub fn build_with_parent<'a>(
@@ -1256,12 +1284,12 @@ pub enum ValueExpression {
 }
 
 impl ValueExpression {
-    pub fn new(n: Node) -> Self {
-        match n.kind() {
-            "field_identifier" => Self::Field(FieldIdentifier::new(n)),
-            "function_expression" => Self::Function(Box::new(FunctionExpression::new(n))),
-            _ => panic_unknown_node(n, "ValueExpression"),
-        }
+    pub fn new(n: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match n.kind() {
+            "field_identifier" => Self::Field(FieldIdentifier::new(n, source)?),
+            "function_expression" => Self::Function(Box::new(FunctionExpression::new(n, source)?)),
+            _ => return Err(unknown_node(n, "ValueExpression")),
+        })
     }
 }
 
@@ -1290,29 +1318,29 @@ pub enum GeoLocationTypeVariant {
 }
 
 impl GeoLocationTypeVariant {
-    pub fn new(node: Node) -> Self {
-        let child = node.first_c();
-        match child.kind() {
-            "field_identifier" => Self::Field(FieldIdentifier::new(child)),
-            "bound_apex_expression" => Self::Bound(BoundApexExpression::new(child)),
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        let child = node.first_c()?;
+        Ok(match child.kind() {
+            "field_identifier" => Self::Field(FieldIdentifier::new(child, source)?),
+            "bound_apex_expression" => Self::Bound(BoundApexExpression::new(child, source)?),
             "identifier" => {
-                let decimals = node.cs_by_k("decimal");
+                let decimals = node.cs_by_k("decimal")?;
                 if decimals.len() != 2 {
-                    panic!(
-                        "expect 2 decimal nodes, found {} in GeoLocationType",
-                        decimals.len()
-                    );
+                    return Err(EnrichError::new(
+                        &node,
+                        format!("expected 2 decimal nodes, found {}", decimals.len()),
+                    ));
                 }
 
                 Self::Func {
-                    function_name: ValueNode::new(child),
-                    decimal1: ValueNode::new(decimals[0]),
-                    decimal2: ValueNode::new(decimals[1]),
+                    function_name: ValueNode::new(child, source)?,
+                    decimal1: ValueNode::new(decimals[0], source)?,
+                    decimal2: ValueNode::new(decimals[1], source)?,
                 }
             }
 
-            _ => panic_unknown_node(child, "GeoLocationType"),
-        }
+            _ => return Err(unknown_node(child, "GeoLocationType")),
+        })
     }
 }
 
@@ -1394,18 +1422,18 @@ pub enum SoqlLiteral {
 }
 
 impl SoqlLiteral {
-    pub fn new(node: Node) -> Self {
-        match node.kind() {
-            "decimal" => Self::Decimal(node.value()),
-            "int" => Self::Int(node.value()),
-            "string_literal" => Self::StringLiteral(node.value()),
-            "boolean" => Self::Boolean(node.value()),
-            "date" => Self::Boolean(node.value()),
-            "date_literal" => Self::DateLiteral(node.value()),
-            "date_literal_with_param" => Self::DWithParam(DateLiteralWithParam::new(node)),
-            "null_literal" => Self::NullLiteral(node.value()),
-            _ => panic_unknown_node(node, "SoqlLiteral"),
-        }
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match node.kind() {
+            "decimal" => Self::Decimal(node.value(source)),
+            "int" => Self::Int(node.value(source)),
+            "string_literal" => Self::StringLiteral(node.value(source)),
+            "boolean" => Self::Boolean(node.value(source)),
+            "date" => Self::Boolean(node.value(source)),
+            "date_literal" => Self::DateLiteral(node.value(source)),
+            "date_literal_with_param" => Self::DWithParam(DateLiteralWithParam::new(node, source)?),
+            "null_literal" => Self::NullLiteral(node.value(source)),
+            _ => return Err(unknown_node(node, "SoqlLiteral")),
+        })
     }
 }
 
@@ -1451,14 +1479,14 @@ pub struct DateLiteralWithParam {
 }
 
 impl DateLiteralWithParam {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "date_literal_with_param");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "date_literal_with_param")?;
 
-        Self {
-            date_literal: node.cvalue_by_k("date_literal").to_uppercase(),
-            param: node.cvalue_by_k("int"),
+        Ok(Self {
+            date_literal: node.cvalue_by_k("date_literal", source)?.to_uppercase(),
+            param: node.cvalue_by_k("int", source)?,
             node_info: NodeInfo::from(&node),
-        }
+        })
     }
 }
 
@@ -1478,13 +1506,13 @@ pub enum SetValue {
 }
 
 impl SetValue {
-    pub fn new(node: Node) -> Self {
-        match node.kind() {
-            "subquery" => Self::Sub(SubQuery::new(node)),
-            "comparable_list" => Self::List(ComparableList::new(node)),
-            "bound_apex_expression" => Self::Bound(BoundApexExpression::new(node)),
-            _ => panic_unknown_node(node, "SetValue"),
-        }
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match node.kind() {
+            "subquery" => Self::Sub(SubQuery::new(node, source)?),
+            "comparable_list" => Self::List(ComparableList::new(node, source)?),
+            "bound_apex_expression" => Self::Bound(BoundApexExpression::new(node, source)?),
+            _ => return Err(unknown_node(node, "SetValue")),
+        })
     }
 }
 
@@ -1511,11 +1539,11 @@ pub enum ComparableListValue {
 }
 
 impl ComparableListValue {
-    pub fn new(node: Node) -> Self {
-        match node.kind() {
-            "bound_apex_expression" => Self::Bound(BoundApexExpression::new(node)),
-            _ => Self::Literal(SoqlLiteral::new(node)),
-        }
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        Ok(match node.kind() {
+            "bound_apex_expression" => Self::Bound(BoundApexExpression::new(node, source)?),
+            _ => Self::Literal(SoqlLiteral::new(node, source)?),
+        })
     }
 }
 
@@ -1539,15 +1567,15 @@ pub enum OffsetClause {
 }
 
 impl OffsetClause {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "offset_clause");
-
-        let first_c = node.first_c();
-        match first_c.kind() {
-            "int" => Self::Int(ValueNode::new(first_c)),
-            "bound_apex_expression" => Self::Bound(BoundApexExpression::new(first_c)),
-            _ => panic_unknown_node(first_c, "OffsetClause"),
-        }
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "offset_clause")?;
+
+        let first_c = node.first_c()?;
+        Ok(match first_c.kind() {
+            "int" => Self::Int(ValueNode::new(first_c, source)?),
+            "bound_apex_expression" => Self::Bound(BoundApexExpression::new(first_c, source)?),
+            _ => return Err(unknown_node(first_c, "OffsetClause")),
+        })
     }
 }
 
@@ -1582,34 +1610,36 @@ pub enum FunctionExpressionVariant {
 }
 
 impl FunctionExpressionVariant {
-    pub fn new(node: Node) -> Self {
-        assert_check(node, "function_expression");
+    pub fn new(node: Node, source: &str) -> Result<Self, EnrichError> {
+        assert_check(node, "function_expression")?;
 
         let function_expression = if node.try_c_by_k("geo_location_type").is_some() {
             Self::WithGEO {
-                function_name: ValueNode::new(node.c_by_n("function_name")),
+                function_name: ValueNode::new(node.c_by_n("function_name")?, source)?,
                 field: node
                     .try_c_by_k("field_identifier")
-                    .map(|n| FieldIdentifier::new(n)),
+                    .map(|n| FieldIdentifier::new(n, source))
+                    .transpose()?,
                 bound: node
                     .try_c_by_k("bound_apex_expression")
-                    .map(|n| BoundApexExpression::new(n)),
-                geo: GeoLocationType::new(node.c_by_k("geo_location_type")),
-                string_literal: ValueNode::new(node.c_by_k("string_literal")),
+                    .map(|n| BoundApexExpression::new(n, source))
+                    .transpose()?,
+                geo: GeoLocationType::new(node.c_by_k("geo_location_type")?, source)?,
+                string_literal: ValueNode::new(node.c_by_k("string_literal")?, source)?,
             }
         } else {
             Self::WithoutGEO {
-                function_name: ValueNode::new(node.c_by_n("function_name")),
+                function_name: ValueNode::new(node.c_by_n("function_name")?, source)?,
                 value_exps: node
                     .children_vec()
                     .into_iter()
                     .skip(1)
-                    .map(|n| ValueExpression::new(n))
-                    .collect(),
+                    .map(|n| ValueExpression::new(n, source))
+                    .collect::<Result<_, _>>()?,
             }
         };
 
-        function_expression
+        Ok(function_expression)
     }
 }
 
@@ -0,0 +1,104 @@
+use crate::error::{char_column, EquivalenceError};
+use tree_sitter::{Node, Tree};
+
+// leaves whose casing the formatter normalizes, see `ValueNodeLowerCase` and
+// `ValueNodeUpperCase`
+const CASE_INSENSITIVE_KINDS: [&str; 6] = [
+    "boolean",
+    "null_literal",
+    "decimal_floating_point_literal",
+    "fields_type",
+    "with_data_cat_filter_type",
+    "date_literal",
+];
+
+/// Walks both trees in the same order and returns the first node of `source`
+/// that has no equal in `output`. Comments are skipped, and so is whitespace,
+/// as it never makes it into the tree.
+pub(crate) fn first_difference(
+    source: &Tree,
+    source_code: &str,
+    output: &Tree,
+    output_code: &str,
+) -> Option<EquivalenceError> {
+    let mut stack = vec![(source.root_node(), output.root_node())];
+
+    while let Some((old, new)) = stack.pop() {
+        if old.kind_id() != new.kind_id() {
+            return Some(divergence(&old, source_code, Some(&new), output_code));
+        }
+        if old.is_named()
+            && old.child_count() == 0
+            && !same_text(&old, source_code, &new, output_code)
+        {
+            return Some(divergence(&old, source_code, Some(&new), output_code));
+        }
+
+        let old_children = children(&old);
+        let new_children = children(&new);
+        if old_children.len() != new_children.len() {
+            let index = old_children
+                .iter()
+                .zip(&new_children)
+                .position(|(o, n)| o.kind_id() != n.kind_id())
+                .unwrap_or(old_children.len().min(new_children.len()));
+            return Some(match (old_children.get(index), new_children.get(index)) {
+                (Some(o), n) => divergence(o, source_code, n, output_code),
+                (None, n) => divergence(&old, source_code, n, output_code),
+            });
+        }
+
+        // reversed, so that the first child is compared first
+        stack.extend(old_children.into_iter().zip(new_children).rev());
+    }
+
+    None
+}
+
+fn children<'t>(node: &Node<'t>) -> Vec<Node<'t>> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra())
+        .collect()
+}
+
+fn text<'s>(node: &Node, code: &'s str) -> &'s str {
+    &code[node.byte_range()]
+}
+
+fn same_text(old: &Node, source_code: &str, new: &Node, output_code: &str) -> bool {
+    let (old_text, new_text) = (text(old, source_code), text(new, output_code));
+    if CASE_INSENSITIVE_KINDS.contains(&old.kind()) {
+        old_text.eq_ignore_ascii_case(new_text)
+    } else {
+        old_text == new_text
+    }
+}
+
+fn divergence(
+    old: &Node,
+    source_code: &str,
+    new: Option<&Node>,
+    output_code: &str,
+) -> EquivalenceError {
+    let start = old.start_position();
+    let found = new.map(|n| {
+        format!(
+            "`{}` node `{}` on output line {}",
+            n.kind(),
+            text(n, output_code).lines().next().unwrap_or(""),
+            n.start_position().row + 1
+        )
+    });
+
+    let snippet = source_code.lines().nth(start.row).unwrap_or("");
+
+    EquivalenceError {
+        path: None,
+        kind: old.kind(),
+        line: start.row + 1,
+        column: char_column(snippet, start.column),
+        snippet: snippet.to_string(),
+        found,
+    }
+}
@@ -0,0 +1,419 @@
+use crate::diff::unified_diff;
+use std::fmt;
+use tree_sitter::Node;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatError {
+    /// The source file could not be read.
+    Io(String),
+    /// The `.afmt.toml` that applies to the source file is invalid.
+    Config(ConfigError),
+    /// The parser produced an error node; the source is not valid Apex.
+    Parse(ParseError),
+    /// The source is valid Apex, but uses syntax afmt does not support yet.
+    Enrich(EnrichError),
+    /// afmt itself failed while formatting otherwise valid source.
+    Internal(String),
+    /// Formatting the output again changed it, see
+    /// [`Formatter::with_idempotency_check`](crate::formatter::Formatter::with_idempotency_check).
+    NotIdempotent { first: String, second: String },
+    /// The formatted output parses to a different tree than the source, see
+    /// [`Formatter::with_safety_check`](crate::formatter::Formatter::with_safety_check).
+    NotEquivalent(EquivalenceError),
+    /// Comments the formatter failed to place in its output, see the
+    /// `erased_comments` config option.
+    LostComments(LostCommentsError),
+}
+
+impl FormatError {
+    /// Short name of the variant, as used in `--report json`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FormatError::Io(_) => "io",
+            FormatError::Config(_) => "config",
+            FormatError::Parse(_) => "parse",
+            FormatError::Enrich(_) => "enrich",
+            FormatError::Internal(_) => "internal",
+            FormatError::NotIdempotent { .. } => "not_idempotent",
+            FormatError::NotEquivalent(_) => "not_equivalent",
+            FormatError::LostComments(_) => "lost_comments",
+        }
+    }
+
+    /// 1-based line and column (in characters) the error points at, if any.
+    /// For `Config`, they point into the `.afmt.toml`.
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match self {
+            FormatError::Config(e) => Some((e.line, e.column)),
+            FormatError::Parse(e) => Some((e.line, e.column)),
+            FormatError::Enrich(e) => Some((e.line, e.column)),
+            FormatError::NotEquivalent(e) => Some((e.line, e.column)),
+            FormatError::LostComments(e) => e.comments.first().map(|c| (c.line, c.column)),
+            _ => None,
+        }
+    }
+
+    /// Attaches the source file path to errors that point into the source.
+    pub fn with_path(self, path: &str) -> Self {
+        match self {
+            FormatError::Parse(e) => FormatError::Parse(ParseError {
+                path: Some(path.to_string()),
+                ..e
+            }),
+            FormatError::Enrich(e) => FormatError::Enrich(EnrichError {
+                path: Some(path.to_string()),
+                ..e
+            }),
+            FormatError::NotEquivalent(e) => FormatError::NotEquivalent(EquivalenceError {
+                path: Some(path.to_string()),
+                ..e
+            }),
+            FormatError::LostComments(e) => FormatError::LostComments(LostCommentsError {
+                path: Some(path.to_string()),
+                ..e
+            }),
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Io(message) => write!(f, "{}", message),
+            FormatError::Config(e) => write!(f, "{}", e),
+            FormatError::Parse(e) => write!(f, "{}", e),
+            FormatError::Enrich(e) => write!(f, "{}", e),
+            FormatError::NotEquivalent(e) => write!(f, "{}", e),
+            FormatError::LostComments(e) => write!(f, "{}", e),
+            FormatError::Internal(message) => write!(f, "internal error: {}", message),
+            FormatError::NotIdempotent { first, second } => write!(
+                f,
+                "error: formatting is not idempotent, a second pass changes the output:\n{}",
+                unified_diff("<formatted>", first, second, false)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+impl From<ParseError> for FormatError {
+    fn from(e: ParseError) -> Self {
+        FormatError::Parse(e)
+    }
+}
+
+impl From<EnrichError> for FormatError {
+    fn from(e: EnrichError) -> Self {
+        FormatError::Enrich(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub path: Option<String>,
+    /// 1-based line of the error node
+    pub line: usize,
+    /// 1-based column (in characters) of the error node
+    pub column: usize,
+    pub kind: &'static str,
+    pub is_missing: bool,
+    pub parent_kind: Option<&'static str>,
+    /// the full source line the error starts on
+    pub source_line: String,
+    /// number of characters of `source_line` covered by the error node
+    pub span: usize,
+}
+
+impl ParseError {
+    pub fn from_node(node: &Node, source_code: &str) -> Self {
+        let start = node.start_position();
+        let source_line = source_code
+            .lines()
+            .nth(start.row)
+            .unwrap_or("")
+            .trim_end()
+            .to_string();
+
+        let line_start = node.start_byte() - start.column;
+        let prefix = source_code.get(line_start..node.start_byte()).unwrap_or("");
+        let column = prefix.chars().count() + 1;
+
+        let line_end = line_start + source_line.len();
+        let span = source_code
+            .get(node.start_byte()..node.end_byte().min(line_end).max(node.start_byte()))
+            .map(|s| s.chars().count())
+            .unwrap_or(0)
+            .max(1);
+
+        Self {
+            path: None,
+            line: start.row + 1,
+            column,
+            kind: node.kind(),
+            is_missing: node.is_missing(),
+            parent_kind: node.parent().map(|p| p.kind()),
+            source_line,
+            span,
+        }
+    }
+
+    /// One-line description, without the location.
+    pub fn message(&self) -> String {
+        let mut message = if self.is_missing {
+            format!("missing `{}`", self.kind)
+        } else {
+            format!("unexpected `{}` node", self.kind)
+        };
+        if let Some(parent) = self.parent_kind {
+            message.push_str(&format!(" in `{}`", parent));
+        }
+        message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "error: {}", self.message())?;
+        write_snippet(
+            f,
+            self.path.as_deref(),
+            self.line,
+            self.column,
+            &self.source_line,
+            Some(self.span),
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// 1-based column, in characters, of the 0-based `byte_column` of `line`
+pub(crate) fn char_column(line: &str, byte_column: usize) -> usize {
+    line.get(..byte_column)
+        .map_or(byte_column, |before| before.chars().count())
+        + 1
+}
+
+// the location and source line of an error as rustc prints them, with `span`
+// characters from `column` underlined when given
+fn write_snippet(
+    f: &mut fmt::Formatter<'_>,
+    path: Option<&str>,
+    line: usize,
+    column: usize,
+    source_line: &str,
+    span: Option<usize>,
+) -> fmt::Result {
+    let gutter = " ".repeat(line.to_string().len());
+    writeln!(
+        f,
+        "{}--> {}:{}:{}",
+        gutter,
+        path.unwrap_or("<source>"),
+        line,
+        column
+    )?;
+    writeln!(f, "{} |", gutter)?;
+    write!(f, "{} | {}", line, source_line)?;
+    if let Some(span) = span {
+        write!(
+            f,
+            "\n{} | {}{}",
+            gutter,
+            " ".repeat(column - 1),
+            "^".repeat(span)
+        )?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnrichError {
+    pub path: Option<String>,
+    /// kind of the node afmt could not handle
+    pub kind: &'static str,
+    pub message: String,
+    /// 1-based line of the node
+    pub line: usize,
+    /// 1-based column (in characters) of the node, in bytes until
+    /// `with_snippet` is called
+    pub column: usize,
+    /// the source line the node starts on
+    pub snippet: String,
+}
+
+impl EnrichError {
+    pub fn new(node: &Node, message: impl ToString) -> Self {
+        let start = node.start_position();
+        Self {
+            path: None,
+            kind: node.kind(),
+            message: message.to_string(),
+            line: start.row + 1,
+            column: start.column + 1,
+            snippet: String::new(),
+        }
+    }
+
+    /// Fills `snippet` with the error's line of `source_code`.
+    pub fn with_snippet(mut self, source_code: &str) -> Self {
+        self.snippet = source_code
+            .lines()
+            .nth(self.line - 1)
+            .unwrap_or("")
+            .to_string();
+        self.column = char_column(&self.snippet, self.column - 1);
+        self
+    }
+}
+
+impl fmt::Display for EnrichError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "error: unsupported `{}` node: {}",
+            self.kind, self.message
+        )?;
+        write_snippet(
+            f,
+            self.path.as_deref(),
+            self.line,
+            self.column,
+            &self.snippet,
+            None,
+        )
+    }
+}
+
+impl std::error::Error for EnrichError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquivalenceError {
+    pub path: Option<String>,
+    /// kind of the first source node the output has no equal for
+    pub kind: &'static str,
+    /// 1-based line of the node
+    pub line: usize,
+    /// 1-based column (in characters) of the node
+    pub column: usize,
+    /// the full source line the node starts on
+    pub snippet: String,
+    /// the output node found in its place, `None` when there is none
+    pub found: Option<String>,
+}
+
+impl fmt::Display for EquivalenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "error: formatted output is not equivalent to the source: `{}` node became {}",
+            self.kind,
+            self.found.as_deref().unwrap_or("nothing")
+        )?;
+        write_snippet(
+            f,
+            self.path.as_deref(),
+            self.line,
+            self.column,
+            &self.snippet,
+            None,
+        )
+    }
+}
+
+impl std::error::Error for EquivalenceError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LostCommentsError {
+    pub path: Option<String>,
+    pub comments: Vec<LostComment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LostComment {
+    /// 1-based line of the comment
+    pub line: usize,
+    /// 1-based column (in characters) of the comment
+    pub column: usize,
+    /// first line of the comment
+    pub text: String,
+}
+
+impl fmt::Display for LostCommentsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "error: formatting would erase {} comment(s)",
+            self.comments.len()
+        )?;
+
+        for comment in &self.comments {
+            writeln!(f)?;
+            write_snippet(
+                f,
+                self.path.as_deref(),
+                comment.line,
+                comment.column,
+                &comment.text,
+                None,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LostCommentsError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub path: Option<String>,
+    /// 1-based line of the offending key or value
+    pub line: usize,
+    /// 1-based column (in characters) of the offending key or value
+    pub column: usize,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(content: &str, offset: usize, message: impl ToString) -> Self {
+        let offset = offset.min(content.len());
+        let before = content.get(..offset).unwrap_or("");
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        Self {
+            path: None,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn from_toml(content: &str, e: &toml::de::Error) -> Self {
+        let offset = e.span().map_or(0, |span| span.start);
+        Self::new(content, offset, e.message())
+    }
+
+    pub fn with_path(self, path: &str) -> Self {
+        Self {
+            path: Some(path.to_string()),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "error: invalid config: {}", self.message)?;
+        write!(
+            f,
+            " --> {}:{}:{}",
+            self.path.as_deref().unwrap_or("<config>"),
+            self.line,
+            self.column
+        )
+    }
+}
+
+impl std::error::Error for ConfigError {}
@@ -0,0 +1,106 @@
+use crate::message_helper::yellow;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+const APEX_EXTENSIONS: [&str; 2] = ["cls", "trigger"];
+
+// directories that never contain source we should touch
+const SKIPPED_DIRS: [&str; 1] = ["node_modules"];
+
+/// Expands the given files, directories and glob patterns into a list of
+/// Apex source files. Directories are walked recursively and only `.cls` and
+/// `.trigger` files are picked up; explicitly named files are kept as-is.
+pub fn collect_source_files(paths: &[String]) -> Result<Vec<String>, String> {
+    let mut files = Vec::new();
+    let mut seen = HashSet::new();
+
+    for path in paths {
+        let mut found = Vec::new();
+
+        if is_glob_pattern(path) {
+            let entries = glob::glob(path)
+                .map_err(|e| format!("Invalid glob pattern: {} {}", yellow(path), e))?;
+            let mut matched = false;
+            for entry in entries {
+                let entry = entry.map_err(|e| format!("Failed to read path: {}", e))?;
+                matched = true;
+                if entry.is_dir() {
+                    walk_dir(&entry, &mut found)?;
+                } else if is_apex_file(&entry) {
+                    found.push(entry.to_string_lossy().to_string());
+                }
+            }
+            if !matched {
+                return Err(format!("No files match: {}", yellow(path)));
+            }
+        } else {
+            let p = Path::new(path);
+            if p.is_dir() {
+                walk_dir(p, &mut found)?;
+            } else if p.is_file() {
+                found.push(path.clone());
+            } else {
+                return Err(format!("Path not found: {}", yellow(path)));
+            }
+        }
+
+        // `./A.cls` and `A.cls` are the same file
+        for file in found {
+            let key = fs::canonicalize(&file).unwrap_or_else(|_| file.clone().into());
+            if seen.insert(key) {
+                files.push(file);
+            }
+        }
+    }
+
+    if files.is_empty() {
+        return Err(format!(
+            "No Apex files found in: {}",
+            yellow(&paths.join(", "))
+        ));
+    }
+
+    Ok(files)
+}
+
+fn walk_dir(dir: &Path, found: &mut Vec<String>) -> Result<(), String> {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .map_err(|e| {
+            format!(
+                "Failed to read directory: {} {}",
+                yellow(&dir.to_string_lossy()),
+                e
+            )
+        })?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+
+    // keep the output stable across platforms
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if name.starts_with('.') || SKIPPED_DIRS.contains(&name) {
+                continue;
+            }
+            walk_dir(&path, found)?;
+        } else if is_apex_file(&path) {
+            found.push(path.to_string_lossy().to_string());
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn is_apex_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| APEX_EXTENSIONS.contains(&ext))
+        .unwrap_or(false)
+}
+
+fn is_glob_pattern(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
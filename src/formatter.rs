@@ -1,25 +1,85 @@
-use crate::context::CommentMap;
 use crate::data_model::*;
-use crate::doc::{pretty_print, PrettyConfig};
+use crate::doc::{dump, pretty_print, trace_choices, PrettyConfig};
 use crate::doc_builder::DocBuilder;
+use crate::equivalence;
+use crate::error::{
+    char_column, ConfigError, FormatError, LostComment, LostCommentsError, ParseError,
+};
 use crate::message_helper::{red, yellow};
+use crate::range::{self, SourceRange};
 use crate::utility::{
-    assert_no_missing_comments, collect_comments, enrich, set_thread_comment_map,
-    set_thread_source_code,
+    build_comment_map, dump_ast, dump_comment_map, enrich, ignore_node, lost_comments,
 };
 use serde::Deserialize;
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::ops::Range;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc;
 use std::thread;
-use std::{fs, path::Path};
+use std::time::{Duration, Instant};
+use toml::Spanned;
 use tree_sitter::{Node, Parser, Tree};
 
+pub const CONFIG_FILE_NAME: &str = ".afmt.toml";
+
+// config discovery never walks above the Salesforce DX project root
+const PROJECT_ROOT_FILE: &str = "sfdx-project.json";
+
+// keys accepted in `.afmt.toml`, keep in sync with the fields of `Config`
+const CONFIG_KEYS: [&str; 8] = [
+    "max_width",
+    "indent_size",
+    "indent_style",
+    "tab_width",
+    "newline_style",
+    "insert_final_newline",
+    "continuation_style",
+    "erased_comments",
+];
+
+const MAX_INDENT_SIZE: u32 = 16;
+
 #[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     #[serde(default = "default_max_width")]
     pub max_width: u32,
 
     #[serde(default = "default_indent_size")]
     pub indent_size: u32,
+
+    #[serde(default)]
+    pub indent_style: IndentStyle,
+
+    // columns a tab counts for when `indent_style` is tabs
+    #[serde(default = "default_tab_width")]
+    pub tab_width: u32,
+
+    #[serde(default)]
+    pub newline_style: NewlineStyle,
+
+    // when false, a final newline is only kept if the source had one
+    #[serde(default = "default_insert_final_newline")]
+    pub insert_final_newline: bool,
+
+    #[serde(default)]
+    pub continuation_style: ContinuationStyle,
+
+    #[serde(default)]
+    pub erased_comments: ErasedComments,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndentStyle {
+    #[default]
+    Spaces,
+    Tabs,
 }
 
 fn default_max_width() -> u32 {
@@ -30,11 +90,75 @@ fn default_indent_size() -> u32 {
     2
 }
 
+fn default_tab_width() -> u32 {
+    4
+}
+
+fn default_insert_final_newline() -> bool {
+    true
+}
+
+// how lines broken inside argument lists, binary expression chains and SOQL
+// `WHERE` conditions continue
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContinuationStyle {
+    // indent continuation lines by one level
+    #[default]
+    Block,
+    // align continuation lines to the opening paren or the first operand
+    Visual,
+}
+
+// what to do with comments the formatter failed to place in its output
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErasedComments {
+    // fail with `FormatError::LostComments`
+    #[default]
+    Error,
+    // keep the original source of the statements or members holding them
+    KeepSource,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NewlineStyle {
+    // use the line ending of the first line of the source
+    #[default]
+    Auto,
+    Lf,
+    Crlf,
+    // the platform's line ending
+    Native,
+}
+
+impl NewlineStyle {
+    pub fn line_ending(self, source_code: &str) -> &'static str {
+        match self {
+            NewlineStyle::Auto => match source_code.find('\n') {
+                Some(i) if source_code[..i].ends_with('\r') => "\r\n",
+                _ => "\n",
+            },
+            NewlineStyle::Lf => "\n",
+            NewlineStyle::Crlf => "\r\n",
+            NewlineStyle::Native if cfg!(windows) => "\r\n",
+            NewlineStyle::Native => "\n",
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             max_width: default_max_width(),
             indent_size: default_indent_size(),
+            indent_style: IndentStyle::default(),
+            tab_width: default_tab_width(),
+            newline_style: NewlineStyle::default(),
+            insert_final_newline: default_insert_final_newline(),
+            continuation_style: ContinuationStyle::default(),
+            erased_comments: ErasedComments::default(),
         }
     }
 }
@@ -43,18 +167,103 @@ impl Config {
     pub fn new(max_width: u32) -> Self {
         Self {
             max_width,
-            indent_size: 2,
+            ..Self::default()
         }
     }
 
-    pub fn from_file(path: &str) -> Result<Self, String> {
-        let content =
-            fs::read_to_string(path).map_err(|e| format!("Failed to read config file: {}", e))?;
+    pub fn from_file(path: &str) -> Result<Self, FormatError> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            FormatError::Io(format!(
+                "Failed to read config file: {} {}",
+                yellow(path),
+                e
+            ))
+        })?;
+        Config::from_toml(&content).map_err(|e| FormatError::Config(e.with_path(path)))
+    }
+
+    /// Parses and validates the content of an `.afmt.toml` file. Unknown keys
+    /// and out of range values are rejected.
+    pub fn from_toml(content: &str) -> Result<Self, ConfigError> {
+        let table: BTreeMap<Spanned<String>, Spanned<toml::Value>> =
+            toml::from_str(content).map_err(|e| ConfigError::from_toml(content, &e))?;
+
+        for key in table.keys() {
+            if !CONFIG_KEYS.contains(&key.get_ref().as_str()) {
+                let mut message = format!("unknown config key `{}`", key.get_ref());
+                if let Some(suggestion) = suggest_key(key.get_ref()) {
+                    message.push_str(&format!(", did you mean `{}`?", suggestion));
+                }
+                return Err(ConfigError::new(content, key.span().start, message));
+            }
+        }
+
         let config: Config =
-            toml::from_str(&content).map_err(|e| format!("Failed to parse config file: {}", e))?;
+            toml::from_str(content).map_err(|e| ConfigError::from_toml(content, &e))?;
+
+        let value_start = |key: &str| {
+            table
+                .iter()
+                .find(|(k, _)| k.get_ref() == key)
+                .map_or(0, |(_, v)| v.span().start)
+        };
+
+        if config.max_width == 0 {
+            return Err(ConfigError::new(
+                content,
+                value_start("max_width"),
+                "`max_width` must be greater than 0",
+            ));
+        }
+
+        if !(1..=MAX_INDENT_SIZE).contains(&config.indent_size) {
+            return Err(ConfigError::new(
+                content,
+                value_start("indent_size"),
+                format!("`indent_size` must be between 1 and {}", MAX_INDENT_SIZE),
+            ));
+        }
+
+        if !(1..=MAX_INDENT_SIZE).contains(&config.tab_width) {
+            return Err(ConfigError::new(
+                content,
+                value_start("tab_width"),
+                format!("`tab_width` must be between 1 and {}", MAX_INDENT_SIZE),
+            ));
+        }
+
         Ok(config)
     }
 
+    /// Finds the nearest `.afmt.toml` in the directory of `file` or any of its
+    /// parents. The walk stops at the directory holding `sfdx-project.json`.
+    pub fn find_config_file(file: &Path) -> Option<PathBuf> {
+        let file = std::path::absolute(file).ok()?;
+        let mut dir = file.parent();
+
+        while let Some(d) = dir {
+            let candidate = d.join(CONFIG_FILE_NAME);
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+            if d.join(PROJECT_ROOT_FILE).is_file() {
+                return None;
+            }
+            dir = d.parent();
+        }
+
+        None
+    }
+
+    /// Config from the nearest `.afmt.toml` of `file`, or the defaults if
+    /// there is none.
+    pub fn discover(file: &Path) -> Result<Self, FormatError> {
+        match Config::find_config_file(file) {
+            Some(config_file) => Config::from_file(&config_file.to_string_lossy()),
+            None => Ok(Config::default()),
+        }
+    }
+
     pub fn max_width(&self) -> u32 {
         self.max_width
     }
@@ -62,12 +271,31 @@ impl Config {
     pub fn indent_size(&self) -> u32 {
         self.indent_size
     }
+
+    // width of a tab when indenting with tabs, `None` when indenting with spaces
+    pub fn tab_width_if_tabs(&self) -> Option<u32> {
+        (self.indent_style == IndentStyle::Tabs).then_some(self.tab_width)
+    }
+
+    // columns added by one level of indentation
+    pub fn indent_width(&self) -> u32 {
+        self.tab_width_if_tabs().unwrap_or(self.indent_size)
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct Formatter {
     config: Config,
     source_files: Vec<String>,
+    jobs: usize,
+    // look up the nearest `.afmt.toml` of each file instead of using `config`
+    discover_config: bool,
+    // files formatted only partially, see `format_ranges`
+    ranges: HashMap<String, Vec<SourceRange>>,
+    // format every output a second time and fail when it changes
+    verify_idempotent: bool,
+    // re-parse every output and fail when its tree differs from the source's
+    verify_equivalent: bool,
     //pub errors: ReportedErrors,
 }
 
@@ -76,126 +304,419 @@ impl Formatter {
         Self {
             config,
             source_files,
+            jobs: default_jobs(),
+            discover_config: false,
+            ranges: HashMap::new(),
+            verify_idempotent: false,
+            verify_equivalent: false,
             //errors: ReportedErrors::default(),
         }
     }
 
+    // number of worker threads used by `format`, defaults to the CPU count
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    // use the nearest `.afmt.toml` of each source file, falling back to
+    // `config` for files that have none
+    pub fn with_config_discovery(mut self) -> Self {
+        self.discover_config = true;
+        self
+    }
+
+    // format only the statements and members of `file` covering `ranges`
+    pub fn with_ranges(mut self, file: &str, ranges: Vec<SourceRange>) -> Self {
+        self.ranges.insert(file.to_string(), ranges);
+        self
+    }
+
+    // fail with `FormatError::NotIdempotent` for files whose formatted output
+    // changes when formatted again; files with ranges are not checked
+    pub fn with_idempotency_check(mut self) -> Self {
+        self.verify_idempotent = true;
+        self
+    }
+
+    // fail with `FormatError::NotEquivalent` for files whose formatted output
+    // does not parse to the same tree as the source
+    pub fn with_safety_check(mut self) -> Self {
+        self.verify_equivalent = true;
+        self
+    }
+
     pub fn config(&self) -> &Config {
         &self.config
     }
 
+    pub fn source_files(&self) -> &[String] {
+        &self.source_files
+    }
+
     pub fn create_from_config(
         config_path: Option<&str>,
         source_files: Vec<String>,
     ) -> Result<Formatter, String> {
-        let config = match config_path {
-            Some(path) => Config::from_file(path)
-                .map_err(|e| format!("{}: {}", yellow(&e.to_string()), path))?,
-            None => Config::default(),
-        };
-        Ok(Formatter::new(config, source_files))
+        match config_path {
+            Some(path) => {
+                let config = Config::from_file(path).map_err(|e| e.to_string())?;
+                Ok(Formatter::new(config, source_files))
+            }
+            None => Ok(Formatter::new(Config::default(), source_files).with_config_discovery()),
+        }
+    }
+
+    // formats all source files on a pool of `jobs` workers; results are
+    // returned in the same order as `source_files`
+    pub fn format(&self) -> Vec<(String, Result<String, FormatError>)> {
+        self.format_timed()
+            .into_iter()
+            .map(|(file, result, _)| (file, result))
+            .collect()
     }
 
-    pub fn format(&self) -> Vec<Result<String, String>> {
+    // same as `format`, with the time spent on each file
+    pub fn format_timed(&self) -> Vec<(String, Result<String, FormatError>, Duration)> {
+        let configs = self.resolve_configs();
+        let workers = self.jobs.min(self.source_files.len()).max(1);
+        let next = AtomicUsize::new(0);
         let (tx, rx) = mpsc::channel();
-        let config = self.config.clone();
-
-        for file in &self.source_files {
-            let tx = tx.clone();
-            let config = config.clone();
-            let file = file.clone();
-
-            thread::spawn(move || {
-                let result = std::panic::catch_unwind(|| {
-                    let source_code = fs::read_to_string(Path::new(&file))
-                        .map_err(|e| {
-                            format!(
-                                "Failed to read file: {} {}",
-                                red(&file),
-                                yellow(e.to_string().as_str())
-                            )
-                        })
-                        .unwrap();
-
-                    Formatter::format_one(&source_code, config)
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                let tx = tx.clone();
+                let next = &next;
+                let configs = &configs;
+
+                scope.spawn(move || {
+                    let mut parser = Formatter::new_parser();
+
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(file) = self.source_files.get(index) else {
+                            break;
+                        };
+                        let start = Instant::now();
+                        let result = match &configs[index] {
+                            Ok(config) => self.format_file(&mut parser, file, config),
+                            Err(e) => Err(e.clone()),
+                        };
+                        tx.send((index, result, start.elapsed()))
+                            .expect("failed to send result in tx");
+                    }
                 });
-                match result {
-                    Ok(result) => {
-                        tx.send(Ok(result)).expect("failed to send result in tx");
+            }
+        });
+
+        drop(tx);
+
+        let mut results: Vec<_> = rx.into_iter().collect();
+        results.sort_by_key(|(index, _, _)| *index);
+        results
+            .into_iter()
+            .map(|(index, result, duration)| (self.source_files[index].clone(), result, duration))
+            .collect()
+    }
+
+    // config of every source file, each `.afmt.toml` is read only once
+    fn resolve_configs(&self) -> Vec<Result<Config, FormatError>> {
+        let mut loaded: HashMap<PathBuf, Result<Config, FormatError>> = HashMap::new();
+
+        self.source_files
+            .iter()
+            .map(|file| {
+                let config_file = self
+                    .discover_config
+                    .then(|| Config::find_config_file(Path::new(file)))
+                    .flatten();
+
+                match config_file {
+                    Some(config_file) => loaded
+                        .entry(config_file)
+                        .or_insert_with_key(|path| Config::from_file(&path.to_string_lossy()))
+                        .clone(),
+                    None => Ok(self.config.clone()),
+                }
+            })
+            .collect()
+    }
+
+    fn format_file(
+        &self,
+        parser: &mut Parser,
+        file: &str,
+        config: &Config,
+    ) -> Result<String, FormatError> {
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let source_code = fs::read_to_string(Path::new(file)).map_err(|e| {
+                FormatError::Io(format!(
+                    "Failed to read file: {} {}",
+                    red(file),
+                    yellow(e.to_string().as_str())
+                ))
+            })?;
+
+            let formatted = match self.ranges.get(file) {
+                Some(ranges) => Formatter::format_ranges_with_parser(
+                    parser,
+                    &source_code,
+                    ranges,
+                    config.clone(),
+                )?,
+                None => {
+                    let formatted =
+                        Formatter::format_with_parser(parser, &source_code, config.clone())?;
+                    if self.verify_idempotent {
+                        Formatter::verify_idempotent_with_parser(parser, &formatted, config)?;
                     }
-                    Err(_) => tx
-                        .send(Err("Thread panicked".to_string()))
-                        .expect("failed to send error in tx"),
+                    formatted
                 }
+            };
+
+            if self.verify_equivalent {
+                Formatter::verify_equivalent_with_parser(parser, &source_code, &formatted)?;
+            }
+            Ok::<_, FormatError>(formatted)
+        }));
+
+        match result {
+            Ok(result) => result.map_err(|e| e.with_path(file)),
+            Err(payload) => {
+                // the parser may have been left mid-parse
+                parser.reset();
+                Err(FormatError::Internal(panic_message(payload)))
+            }
+        }
+    }
+
+    pub fn format_one(source_code: &str, config: Config) -> Result<String, FormatError> {
+        Formatter::format_with_parser(&mut Formatter::new_parser(), source_code, config)
+    }
+
+    pub fn format_with_parser(
+        parser: &mut Parser,
+        source_code: &str,
+        config: Config,
+    ) -> Result<String, FormatError> {
+        let line_ending = config.newline_style.line_ending(source_code);
+        // the pipeline only deals with `\n`, line endings are restored at the end
+        let source_code = &normalize_newlines(source_code);
+
+        let ast_tree = Formatter::parse_with(parser, source_code)?;
+        let (mut result, _) = Formatter::format_tree(&ast_tree, source_code, &config, Vec::new())?;
+
+        if !config.insert_final_newline && !source_code.ends_with('\n') {
+            result.truncate(result.trim_end_matches('\n').len());
+        }
+
+        Ok(restore_line_endings(result, line_ending))
+    }
+
+    // formats `formatted` again and fails when the second pass changes it
+    pub fn verify_idempotent_with_parser(
+        parser: &mut Parser,
+        formatted: &str,
+        config: &Config,
+    ) -> Result<(), FormatError> {
+        let second =
+            Formatter::format_with_parser(parser, formatted, config.clone()).map_err(|e| {
+                FormatError::Internal(format!("formatted output fails to format again: {}", e))
+            })?;
+
+        if second != formatted {
+            return Err(FormatError::NotIdempotent {
+                first: formatted.to_string(),
+                second,
             });
         }
+        Ok(())
+    }
 
-        drop(tx);
+    // parses `source_code` and `formatted` and fails at the first node where
+    // the trees differ, ignoring whitespace, comments and normalized casing
+    pub fn verify_equivalent_with_parser(
+        parser: &mut Parser,
+        source_code: &str,
+        formatted: &str,
+    ) -> Result<(), FormatError> {
+        let source_tree = Formatter::parse_with(parser, source_code)?;
+        let output_tree = Formatter::parse_with(parser, formatted).map_err(|e| {
+            FormatError::Internal(format!("formatted output does not parse: {}", e))
+        })?;
+
+        match equivalence::first_difference(&source_tree, source_code, &output_tree, formatted) {
+            Some(e) => Err(FormatError::NotEquivalent(e)),
+            None => Ok(()),
+        }
+    }
 
-        rx.into_iter().collect()
+    pub fn format_ranges(
+        source_code: &str,
+        ranges: &[SourceRange],
+        config: Config,
+    ) -> Result<String, FormatError> {
+        Formatter::format_ranges_with_parser(
+            &mut Formatter::new_parser(),
+            source_code,
+            ranges,
+            config,
+        )
     }
 
-    pub fn format_one(source_code: &str, config: Config) -> String {
-        let ast_tree = Formatter::parse(source_code);
-        set_thread_source_code(source_code.to_string()); // important to set thread level source code now;
+    // only the statements and members covering `ranges` are formatted, every
+    // other byte of `source_code` is kept
+    pub fn format_ranges_with_parser(
+        parser: &mut Parser,
+        source_code: &str,
+        ranges: &[SourceRange],
+        config: Config,
+    ) -> Result<String, FormatError> {
+        let line_ending = config.newline_style.line_ending(source_code);
+        let ranges: Vec<_> = ranges
+            .iter()
+            .map(|r| r.without_carriage_returns(source_code))
+            .collect();
+        let source_code = &normalize_newlines(source_code);
+
+        let ast_tree = Formatter::parse_with(parser, source_code)?;
+        let nodes: Vec<_> = ranges
+            .iter()
+            .flat_map(|r| range::covering_nodes(&ast_tree, &r.to_bytes(source_code)))
+            .collect();
+        if nodes.is_empty() {
+            return Ok(restore_line_endings(source_code.to_string(), line_ending));
+        }
 
-        let mut cursor = ast_tree.walk();
-        let mut comment_map = CommentMap::new();
-        collect_comments(&mut cursor, &mut comment_map);
-        set_thread_comment_map(comment_map); // important to set thread level comment map;
+        let (output, marks) =
+            Formatter::format_tree(&ast_tree, source_code, &config, range::unit_ids(&nodes))?;
+        let result = range::splice(source_code, &nodes, &output, &marks);
 
+        Ok(restore_line_endings(result, line_ending))
+    }
+
+    // formats the whole tree, recording where the output of `marked` nodes starts and ends
+    fn format_tree(
+        ast_tree: &Tree,
+        source_code: &str,
+        config: &Config,
+        marked: Vec<usize>,
+    ) -> Result<(String, HashMap<usize, Range<usize>>), FormatError> {
         // traverse the tree to build enriched data
-        let root: Root = enrich(&ast_tree);
+        let root: Root = enrich(ast_tree, source_code)?;
 
-        // traverse enriched data and create pretty print combinators
-        let c = PrettyConfig::new(config.indent_size);
-        let b = DocBuilder::new(c);
-        let doc_ref = root.build(&b);
+        // statements and members printed as in the source, see `ErasedComments`
+        let mut kept: Vec<Node> = Vec::new();
 
-        let result = pretty_print(doc_ref, config.max_width);
+        loop {
+            let mut comment_map = build_comment_map(ast_tree, source_code)?;
+            for node in &kept {
+                ignore_node(node, &mut comment_map);
+            }
 
-        //print_comment_map(&ast_tree);
+            // traverse enriched data and create pretty print combinators
+            let b = DocBuilder::new(pretty_config(config), comment_map, source_code)
+                .with_marked(marked.clone());
+            let doc_ref = root.build(&b);
 
-        assert_no_missing_comments();
+            let result = pretty_print(doc_ref, config.max_width, config.tab_width_if_tabs());
 
-        result
+            let lost = lost_comments(b.comment_map());
+            if lost.is_empty() {
+                return Ok(result);
+            }
+
+            // only one retry, comments lost again are lost for another reason
+            if config.erased_comments == ErasedComments::KeepSource && kept.is_empty() {
+                kept = lost
+                    .iter()
+                    .flat_map(|c| {
+                        range::covering_nodes(ast_tree, &(c.range.start_byte..c.range.end_byte))
+                    })
+                    .filter(|node| node.parent().is_some())
+                    .collect();
+                if !kept.is_empty() {
+                    continue;
+                }
+            }
+
+            return Err(FormatError::LostComments(LostCommentsError {
+                path: None,
+                comments: lost
+                    .iter()
+                    .map(|c| {
+                        let point = c.range.start_point;
+                        let line = source_code.lines().nth(point.row).unwrap_or("");
+                        LostComment {
+                            line: point.row + 1,
+                            column: char_column(line, point.column),
+                            text: c.value.lines().next().unwrap_or("").to_string(),
+                        }
+                    })
+                    .collect(),
+            }));
+        }
+    }
+
+    // the `Doc` tree built for `source_code`, one node per line; with
+    // `annotate_choices`, every `Choice` is marked with the branch printed
+    pub fn dump_doc(
+        source_code: &str,
+        config: &Config,
+        annotate_choices: bool,
+    ) -> Result<String, FormatError> {
+        let source_code = &normalize_newlines(source_code);
+        let ast_tree = Formatter::parse(source_code)?;
+        let comment_map = build_comment_map(&ast_tree, source_code)?;
+        let root: Root = enrich(&ast_tree, source_code)?;
+
+        let b = DocBuilder::new(pretty_config(config), comment_map, source_code);
+        let doc_ref = root.build(&b);
+
+        let choices = annotate_choices
+            .then(|| trace_choices(doc_ref, config.max_width, config.tab_width_if_tabs()));
+        Ok(dump(doc_ref, choices.as_ref()))
+    }
+
+    // the syntax tree of `source_code` as the formatter sees it, parse errors
+    // included, see `afmt inspect --ast`
+    pub fn inspect_ast(source_code: &str) -> String {
+        let source_code: &str = &normalize_newlines(source_code);
+        let ast_tree = Formatter::new_parser().parse(source_code, None).unwrap();
+        dump_ast(&ast_tree, source_code)
+    }
+
+    // the comments attached to each node, see `afmt inspect --comments`
+    pub fn inspect_comments(source_code: &str) -> Result<String, FormatError> {
+        let source_code: &str = &normalize_newlines(source_code);
+        let ast_tree = Formatter::new_parser().parse(source_code, None).unwrap();
+        let comment_map = build_comment_map(&ast_tree, source_code)?;
+        Ok(dump_comment_map(&ast_tree, &comment_map, source_code))
     }
 
-    pub fn parse(source_code: &str) -> Tree {
+    pub fn new_parser() -> Parser {
         let mut parser = Parser::new();
         let language_fn = tree_sitter_sfapex::apex::LANGUAGE;
         parser
             .set_language(&language_fn.into())
             .expect("Error loading Apex parser");
+        parser
+    }
+
+    pub fn parse(source_code: &str) -> Result<Tree, ParseError> {
+        Formatter::parse_with(&mut Formatter::new_parser(), source_code)
+    }
 
+    pub fn parse_with(parser: &mut Parser, source_code: &str) -> Result<Tree, ParseError> {
         let ast_tree = parser.parse(source_code, None).unwrap();
         let root_node = &ast_tree.root_node();
 
         if root_node.has_error() {
-            if let Some(error_node) = Self::find_last_error_node(root_node) {
-                let error_snippet = &source_code[error_node.start_byte()..error_node.end_byte()];
-                println!(
-                    "Error in node kind: {}, at byte range: {}-{}, snippet: {}",
-                    yellow(error_node.kind()),
-                    error_node.start_byte(),
-                    error_node.end_byte(),
-                    error_snippet,
-                );
-                if let Some(p) = error_node.parent() {
-                    let parent_snippet = &source_code[p.start_byte()..p.end_byte()];
-                    println!(
-                        "Parent node kind: {}, at byte range: {}-{}, snippet: {}",
-                        yellow(p.kind()),
-                        p.start_byte(),
-                        p.end_byte(),
-                        parent_snippet,
-                    );
-                }
-            }
-            panic!("{}", red("Parser encounters an error node in the tree."));
+            let error_node = Self::find_last_error_node(root_node).unwrap_or(*root_node);
+            return Err(ParseError::from_node(&error_node, source_code));
         }
 
-        ast_tree
+        Ok(ast_tree)
     }
 
     fn find_last_error_node<'tree>(node: &Node<'tree>) -> Option<Node<'tree>> {
@@ -216,3 +737,50 @@ impl Formatter {
         last_error_node // Return the last (deepest) error node
     }
 }
+
+fn pretty_config(config: &Config) -> PrettyConfig {
+    PrettyConfig::new(
+        config.indent_width(),
+        config.continuation_style == ContinuationStyle::Visual,
+    )
+}
+
+fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+fn restore_line_endings(result: String, line_ending: &str) -> String {
+    if line_ending == "\n" {
+        result
+    } else {
+        result.replace('\n', line_ending)
+    }
+}
+
+fn normalize_newlines(source_code: &str) -> Cow<'_, str> {
+    if source_code.contains("\r\n") {
+        Cow::Owned(source_code.replace("\r\n", "\n"))
+    } else {
+        Cow::Borrowed(source_code)
+    }
+}
+
+// closest known config key to a misspelled one
+fn suggest_key(key: &str) -> Option<&'static str> {
+    CONFIG_KEYS
+        .iter()
+        .map(|known| (*known, strsim::damerau_levenshtein(key, known)))
+        .filter(|(known, distance)| *distance <= known.len() / 3)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(known, _)| known)
+}
+
+pub(crate) fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Thread panicked".to_string()
+    }
+}
@@ -0,0 +1,128 @@
+use crate::files::is_apex_file;
+use crate::message_helper::yellow;
+use crate::range::SourceRange;
+use std::path::Path;
+use std::process::Command;
+
+// changed Apex files with the line ranges changed in each
+pub type ChangedFiles = Vec<(String, Vec<SourceRange>)>;
+
+/// Apex files changed in the working tree (or the index with `staged`)
+/// relative to `rev`, with the line ranges they add. Needs a local `git`.
+///
+/// With `staged`, the line numbers are those of the index, while the working
+/// tree file is what gets formatted; files with unstaged changes are refused.
+pub fn changed_ranges(rev: Option<&str>, staged: bool) -> Result<ChangedFiles, String> {
+    let mut args = vec!["--unified=0"];
+    if staged {
+        args.push("--cached");
+    }
+    args.extend(rev);
+    let changed = parse_patch(&git_diff(&args)?);
+
+    if staged {
+        let unstaged = git_diff(&["--name-only"])?;
+        if let Some((path, _)) = changed
+            .iter()
+            .find(|(path, _)| unstaged.lines().any(|line| line == path))
+        {
+            return Err(format!(
+                "--staged: {} has unstaged changes, stage or stash them first",
+                yellow(path)
+            ));
+        }
+    }
+
+    Ok(changed)
+}
+
+// output of `git diff` with `args`, paths relative to the current directory
+fn git_diff(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(["diff", "--no-color", "--no-ext-diff", "--relative"])
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git diff failed: {}",
+            yellow(String::from_utf8_lossy(&output.stderr).trim())
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Line ranges added or changed by a unified diff, per Apex file: the `+`
+/// lines of each hunk, not the context around them. Files that were deleted,
+/// or that only lost lines, are left out.
+pub fn parse_patch(patch: &str) -> ChangedFiles {
+    let mut files: ChangedFiles = Vec::new();
+    let mut current: Option<String> = None;
+    // lines of the current hunk still to read on each side, and the number of
+    // the next line on the new side
+    let (mut old_left, mut new_left, mut next_line) = (0, 0, 0);
+
+    for line in patch.lines() {
+        if old_left > 0 || new_left > 0 {
+            match line.chars().next() {
+                Some('+') => {
+                    new_left -= 1;
+                    if let Some(path) = &current {
+                        add_line(&mut files, path, next_line);
+                    }
+                    next_line += 1;
+                }
+                Some('-') => old_left -= 1,
+                Some('\\') => {}
+                _ => {
+                    old_left -= 1;
+                    new_left -= 1;
+                    next_line += 1;
+                }
+            }
+            // a malformed hunk ends where one of its sides runs out
+            if old_left < 0 || new_left < 0 {
+                (old_left, new_left) = (0, 0);
+            }
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            let path = path.split('\t').next().unwrap_or(path);
+            let path = path.strip_prefix("b/").unwrap_or(path);
+            current =
+                (path != "/dev/null" && is_apex_file(Path::new(path))).then(|| path.to_string());
+        } else if let Some(hunk) = line.strip_prefix("@@ ") {
+            if let Some(((_, old_count), (first, new_count))) = parse_hunk_header(hunk) {
+                (old_left, new_left, next_line) = (old_count as isize, new_count as isize, first);
+            }
+        }
+    }
+
+    files
+}
+
+// extends the last range of `path` when `line` follows it
+fn add_line(files: &mut ChangedFiles, path: &str, line: usize) {
+    match files.last_mut() {
+        Some((last, ranges)) if last == path => match ranges.last_mut() {
+            Some(SourceRange::Lines(_, end)) if *end + 1 == line => *end = line,
+            _ => ranges.push(SourceRange::Lines(line, line)),
+        },
+        _ => files.push((path.to_string(), vec![SourceRange::Lines(line, line)])),
+    }
+}
+
+// both sides of `-12,3 +14,5 @@ ...`, each as (first line, line count)
+fn parse_hunk_header(hunk: &str) -> Option<((usize, usize), (usize, usize))> {
+    let mut sides = hunk.split_whitespace();
+    let old_side = sides.next()?.strip_prefix('-')?;
+    let new_side = sides.next()?.strip_prefix('+')?;
+
+    Some((parse_hunk_side(old_side)?, parse_hunk_side(new_side)?))
+}
+
+fn parse_hunk_side(side: &str) -> Option<(usize, usize)> {
+    match side.split_once(',') {
+        Some((first, count)) => Some((first.parse().ok()?, count.parse().ok()?)),
+        None => Some((side.parse().ok()?, 1)),
+    }
+}
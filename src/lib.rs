@@ -2,25 +2,85 @@ mod accessor;
 pub mod args;
 mod context;
 mod data_model;
+pub mod diff;
 mod doc;
 mod doc_builder;
 mod enum_def;
+mod equivalence;
+pub mod error;
+pub mod files;
 pub mod formatter;
+pub mod git;
+pub mod lsp;
 pub mod message_helper;
+pub mod range;
 mod utility;
-use formatter::Config;
-use formatter::Formatter;
+pub use error::FormatError;
+pub use formatter::Config;
+use formatter::{panic_message, Formatter};
+pub use range::SourceRange;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 
 use wasm_bindgen::prelude::*;
 
-pub fn format(f: Formatter) -> Vec<Result<String, String>> {
+pub fn format(f: Formatter) -> Vec<(String, Result<String, FormatError>)> {
     f.format()
 }
 
+/// Formats Apex `source_code` with the given `config`.
+///
+/// Nothing is read from or written to the filesystem and nothing is printed to
+/// stdout. Invalid Apex comes back as [`FormatError::Parse`], syntax afmt does
+/// not support yet as [`FormatError::Enrich`], and any other failure inside
+/// afmt as [`FormatError::Internal`].
+///
+/// ```
+/// let config = afmt::Config::default();
+/// let formatted = afmt::format_source("class A{}", &config).unwrap();
+/// assert_eq!(formatted, "class A {\n}\n");
+/// ```
+pub fn format_source(source_code: &str, config: &Config) -> Result<String, FormatError> {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        Formatter::format_one(source_code, config.clone())
+    }));
+
+    match result {
+        Ok(result) => result,
+        Err(payload) => Err(FormatError::Internal(panic_message(payload))),
+    }
+}
+
+/// Formats only the statements and members of `source_code` that cover
+/// `ranges`; all other bytes are returned unchanged.
+///
+/// ```
+/// use afmt::SourceRange;
+///
+/// let config = afmt::Config::default();
+/// let source = "class A{\n  Integer  a=1;\n  Integer  b=2;\n}\n";
+/// let formatted =
+///     afmt::format_source_ranges(source, &[SourceRange::Lines(2, 2)], &config).unwrap();
+/// assert_eq!(formatted, "class A{\n  Integer a = 1;\n  Integer  b=2;\n}\n");
+/// ```
+pub fn format_source_ranges(
+    source_code: &str,
+    ranges: &[SourceRange],
+    config: &Config,
+) -> Result<String, FormatError> {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        Formatter::format_ranges(source_code, ranges, config.clone())
+    }));
+
+    match result {
+        Ok(result) => result,
+        Err(payload) => Err(FormatError::Internal(panic_message(payload))),
+    }
+}
+
 #[wasm_bindgen]
 pub fn greet(source_code: &str) -> String {
     let config = Config::default();
-    Formatter::format_one(source_code, config)
+    format_source(source_code, &config).unwrap_or_else(|e| e.to_string())
 }
 
 //#[wasm_bindgen]
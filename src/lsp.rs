@@ -0,0 +1,357 @@
+//! A minimal Language Server speaking JSON-RPC over stdio.
+//!
+//! Supports full document sync, `textDocument/formatting`,
+//! `textDocument/rangeFormatting` and parse errors as diagnostics. The config
+//! of a document is its nearest `.afmt.toml`, see [`Config::discover`].
+
+use crate::error::{FormatError, ParseError};
+use crate::formatter::{panic_message, Config, Formatter};
+use crate::message_helper::strip_colors;
+use crate::range::SourceRange;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use tree_sitter::Parser;
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_PARAMS: i64 = -32602;
+const METHOD_NOT_FOUND: i64 = -32601;
+const REQUEST_FAILED: i64 = -32803;
+
+const DIAGNOSTIC_ERROR: u32 = 1;
+
+type RpcError = (i64, String);
+
+/// Serves one client on `input`/`output` until it sends `exit`.
+pub fn run(mut input: impl BufRead, mut output: impl Write) -> Result<(), String> {
+    let mut server = Server::new();
+
+    loop {
+        let message = match read_message(&mut input) {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            // the request id is unknown, so the error goes out with a null id
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                let response = json!({
+                    "jsonrpc": "2.0",
+                    "id": Value::Null,
+                    "error": { "code": PARSE_ERROR, "message": e.to_string() },
+                });
+                write_message(&mut output, &response).map_err(|e| e.to_string())?;
+                continue;
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            continue; // a response, the server never sends requests
+        };
+        let params = message.get("params").unwrap_or(&Value::Null);
+
+        if let Some(id) = message.get("id") {
+            let response = match server.handle_request(method, params) {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                Err((code, message)) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": code, "message": message },
+                }),
+            };
+            write_message(&mut output, &response).map_err(|e| e.to_string())?;
+        } else if method == "exit" {
+            return match server.shutdown {
+                true => Ok(()),
+                false => Err("exit received before shutdown".to_string()),
+            };
+        } else {
+            for notification in server.handle_notification(method, params) {
+                write_message(&mut output, &notification).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+struct Server {
+    parser: Parser,
+    documents: HashMap<String, String>,
+    workspace_roots: Vec<PathBuf>,
+    shutdown: bool,
+}
+
+impl Server {
+    fn new() -> Self {
+        Self {
+            parser: Formatter::new_parser(),
+            documents: HashMap::new(),
+            workspace_roots: Vec::new(),
+            shutdown: false,
+        }
+    }
+
+    fn handle_request(&mut self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => {
+                self.workspace_roots = workspace_roots(params);
+                Ok(json!({
+                    "capabilities": {
+                        "textDocumentSync": 1, // full
+                        "documentFormattingProvider": true,
+                        "documentRangeFormattingProvider": true,
+                    },
+                    "serverInfo": { "name": "afmt", "version": env!("CARGO_PKG_VERSION") },
+                }))
+            }
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/formatting" => self.format(params, None),
+            "textDocument/rangeFormatting" => {
+                let range = params.get("range").ok_or_else(|| missing("range"))?;
+                self.format(params, Some(range))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("unsupported method: {}", method))),
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(uri.to_string(), text.to_string());
+                vec![self.diagnostics(uri)]
+            }
+            "textDocument/didChange" => {
+                // full sync: the last change holds the whole document
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes
+                    .and_then(|c| c.last())
+                    .and_then(|c| c["text"].as_str())
+                {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                }
+                vec![self.diagnostics(uri)]
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                vec![publish_diagnostics(uri, Vec::new())]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn diagnostics(&mut self, uri: &str) -> Value {
+        let text = self.documents.get(uri).map_or("", |t| t.as_str());
+        let diagnostics = match Formatter::parse_with(&mut self.parser, text) {
+            Ok(_) => Vec::new(),
+            Err(e) => vec![parse_error_diagnostic(text, &e)],
+        };
+        publish_diagnostics(uri, diagnostics)
+    }
+
+    fn format(&mut self, params: &Value, range: Option<&Value>) -> Result<Value, RpcError> {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .ok_or_else(|| missing("textDocument.uri"))?;
+        let text = self
+            .documents
+            .get(uri)
+            .ok_or_else(|| (REQUEST_FAILED, format!("document not open: {}", uri)))?;
+        let config = self
+            .config_for(uri)
+            .map_err(|e| (REQUEST_FAILED, strip_colors(&e.to_string())))?;
+
+        let ranges = match range {
+            Some(range) => {
+                let start = offset_at(text, &range["start"]).ok_or_else(|| missing("range"))?;
+                let end = offset_at(text, &range["end"]).ok_or_else(|| missing("range"))?;
+                vec![SourceRange::Bytes(start..end)]
+            }
+            None => Vec::new(),
+        };
+
+        let parser = &mut self.parser;
+        let result = catch_unwind(AssertUnwindSafe(|| match range {
+            Some(_) => Formatter::format_ranges_with_parser(parser, text, &ranges, config),
+            None => Formatter::format_with_parser(parser, text, config),
+        }));
+        let formatted = match result {
+            Ok(result) => result,
+            Err(payload) => {
+                self.parser.reset();
+                Err(FormatError::Internal(panic_message(payload)))
+            }
+        }
+        .map_err(|e| (REQUEST_FAILED, strip_colors(&e.to_string())))?;
+
+        if formatted == *text {
+            return Ok(json!([]));
+        }
+
+        // a single edit replacing the whole document
+        Ok(json!([{
+            "range": {
+                "start": { "line": 0, "character": 0 },
+                "end": position_at(text, text.len()),
+            },
+            "newText": formatted,
+        }]))
+    }
+
+    // nearest `.afmt.toml` of the document, or of the workspace for unsaved ones
+    fn config_for(&self, uri: &str) -> Result<Config, FormatError> {
+        match uri_to_path(uri) {
+            Some(path) => Config::discover(&path),
+            None => match self.workspace_roots.first() {
+                Some(root) => Config::discover(&root.join("untitled.cls")),
+                None => Ok(Config::default()),
+            },
+        }
+    }
+}
+
+fn missing(name: &str) -> RpcError {
+    (INVALID_PARAMS, format!("missing or invalid `{}`", name))
+}
+
+fn workspace_roots(params: &Value) -> Vec<PathBuf> {
+    let folders = params["workspaceFolders"].as_array();
+    let mut roots: Vec<PathBuf> = folders
+        .into_iter()
+        .flatten()
+        .filter_map(|folder| folder["uri"].as_str().and_then(uri_to_path))
+        .collect();
+
+    if roots.is_empty() {
+        if let Some(root) = params["rootUri"].as_str().and_then(uri_to_path) {
+            roots.push(root);
+        }
+    }
+    roots
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+fn parse_error_diagnostic(text: &str, e: &ParseError) -> Value {
+    // `column` and `span` count characters, LSP counts UTF-16 code units
+    let line = text.lines().nth(e.line - 1).unwrap_or("");
+    let mut chars = line.chars();
+    let start: usize = chars.by_ref().take(e.column - 1).map(char::len_utf16).sum();
+    let end = start + chars.take(e.span).map(char::len_utf16).sum::<usize>();
+
+    json!({
+        "range": {
+            "start": { "line": e.line - 1, "character": start },
+            "end": { "line": e.line - 1, "character": end },
+        },
+        "severity": DIAGNOSTIC_ERROR,
+        "source": "afmt",
+        "message": e.message(),
+    })
+}
+
+// byte offset of an LSP position, whose `character` counts UTF-16 code units
+fn offset_at(text: &str, position: &Value) -> Option<usize> {
+    let line = position["line"].as_u64()? as usize;
+    let character = position["character"].as_u64()? as usize;
+
+    let line_start = if line == 0 {
+        0
+    } else {
+        text.match_indices('\n').nth(line - 1).map(|(i, _)| i + 1)?
+    };
+
+    let mut units = 0;
+    for (i, c) in text[line_start..].char_indices() {
+        if units >= character || c == '\n' {
+            return Some(line_start + i);
+        }
+        units += c.len_utf16();
+    }
+    Some(text.len())
+}
+
+fn position_at(text: &str, offset: usize) -> Value {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    json!({
+        "line": before.matches('\n').count(),
+        "character": before[line_start..].encode_utf16().count(),
+    })
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let path = percent_decode(path);
+    // `file:///C:/dir` on Windows
+    let path = match path.as_bytes() {
+        [b'/', _, b':', ..] => path[1..].to_string(),
+        _ => path,
+    };
+    Some(Path::new(&path).to_path_buf())
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(Ok(byte)) = s.get(i + 1..i + 3).map(|h| u8::from_str_radix(h, 16)) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = content_length
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
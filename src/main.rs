@@ -1,53 +1,351 @@
-use afmt::args::{get_args, Args};
-use afmt::format;
-use afmt::formatter::Formatter;
+use afmt::args::{get_args, Args, Inspect};
+use afmt::diff::{changed_line_counts, unified_diff};
+use afmt::error::FormatError;
+use afmt::files::collect_source_files;
+use afmt::formatter::{Config, Formatter};
+use afmt::git::{changed_ranges, parse_patch, ChangedFiles};
+use afmt::message_helper::strip_colors;
 use log::error;
 use log::info;
-use std::time::Instant;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::io::{self, IsTerminal, Read};
+use std::path::Path;
+use std::time::{Duration, Instant};
 use std::{fs, process};
 
+// `--check` found files that are not formatted yet
+const EXIT_UNFORMATTED: i32 = 2;
+// `--verify-idempotent` found files that change when formatted again
+const EXIT_NOT_IDEMPOTENT: i32 = 3;
+
 fn main() {
     let start = Instant::now();
     info!("starting up");
 
-    let result = run(get_args());
+    let args = get_args();
+    let quiet = args.stdin || args.dump_doc || args.inspect.is_some() || args.report.is_some();
+    let result = if let Some(inspect) = args.inspect {
+        run_inspect(inspect)
+    } else if args.dump_doc {
+        run_dump_doc(args)
+    } else if args.stdin {
+        run_stdin(args)
+    } else {
+        run(args)
+    };
 
     match result {
-        Ok(_) => {
+        Ok(0) if quiet => process::exit(0),
+        Ok(0) => {
             println!("Afmt completed successfully.");
             let duration = start.elapsed();
             println!("\nExecution time: {:?}", duration);
             process::exit(0);
         }
+        Ok(code) => process::exit(code),
         Err(e) => {
             error!("Error: {}", e);
+            eprintln!("{}", e);
             process::exit(1);
         }
     }
 }
 
-fn run(args: Args) -> Result<(), String> {
-    let formatter = Formatter::create_from_config(args.config.as_deref(), vec![args.path.clone()])?;
-    let results = format(formatter);
+fn run(args: Args) -> Result<i32, String> {
+    let changed = changed_files(&args)?;
+    let source_files = match changed {
+        Some(ref changed) if changed.is_empty() => {
+            if args.report.is_none() {
+                println!("No changed Apex files.");
+            }
+            return Ok(0);
+        }
+        Some(ref changed) => changed.iter().map(|(path, _)| path.clone()).collect(),
+        None => collect_source_files(&args.paths)?,
+    };
+    let mut formatter = Formatter::create_from_config(args.config.as_deref(), source_files)?;
+    if let Some(jobs) = args.jobs {
+        formatter = formatter.with_jobs(jobs);
+    }
+    if args.verify_idempotent {
+        formatter = formatter.with_idempotency_check();
+    }
+    if args.safe {
+        formatter = formatter.with_safety_check();
+    }
+    for (path, ranges) in changed.into_iter().flatten() {
+        formatter = formatter.with_ranges(&path, ranges);
+    }
+    if let Some(ref range) = args.range {
+        let [path] = formatter.source_files() else {
+            return Err("--range and --lines need exactly one file".to_string());
+        };
+        let path = path.clone();
+        formatter = formatter.with_ranges(&path, vec![range.clone()]);
+    }
+    let results = formatter.format_timed();
+    let report = args.report.is_some();
+    let mut unformatted = 0;
+    let mut not_idempotent = 0;
+    let mut not_equivalent = 0;
+    let mut failed = 0;
+    let color = io::stdout().is_terminal();
+
+    // every result is handled, an error in one file does not hide the others
+    for (path, mut result, duration) in results {
+        let mut original = None;
+        if result.is_ok() && (report || args.check || args.diff) {
+            match fs::read_to_string(&path) {
+                Ok(content) => original = Some(content),
+                Err(e) => {
+                    result = Err(FormatError::Io(format!(
+                        "Failed to read file {}: {}",
+                        path, e
+                    )))
+                }
+            }
+        }
+        // written before reporting, so that a failed write is reported as such
+        if args.write && !args.check && !args.diff {
+            if let Ok(ref value) = result {
+                if let Err(e) = fs::write(&path, value) {
+                    result = Err(FormatError::Io(format!(
+                        "Failed to write formatted content to {}: {}",
+                        path, e
+                    )));
+                }
+            }
+        }
+        if report {
+            let record = report_record(&path, &result, original.as_deref(), duration);
+            println!("{}", record);
+        }
 
-    for (index, result) in results.iter().enumerate() {
         match result {
             Ok(value) => {
-                if args.write {
-                    fs::write(&args.path, value).map_err(|e| {
-                        format!("Failed to write formatted content to {}: {}", args.path, e)
-                    })?;
-                    println!("Formatted content written back to: {}\n", args.path);
-                } else {
-                    println!("Result {}: Ok\n{}", index, value);
+                if args.check || args.diff {
+                    if original.as_deref() != Some(value.as_str()) {
+                        unformatted += 1;
+                        if report {
+                            continue;
+                        }
+                        if args.diff {
+                            let original = original.as_deref().unwrap_or_default();
+                            print!("{}", unified_diff(&path, original, &value, color));
+                        } else {
+                            println!("{}", path);
+                        }
+                    }
+                } else if args.write {
+                    if !report {
+                        println!("Formatted content written back to: {}\n", path);
+                    }
+                } else if !report {
+                    println!("Result {}: Ok\n{}", path, value);
                 }
             }
+            Err(FormatError::NotIdempotent { first, second }) => {
+                if !report {
+                    println!("{} changes when formatted again:", path);
+                    print!("{}", unified_diff(&path, &first, &second, color));
+                }
+                not_idempotent += 1;
+            }
+            Err(e @ FormatError::NotEquivalent(_)) => {
+                // keep going, every other file is still safe to write
+                if !report {
+                    eprintln!("{}\n", e);
+                }
+                not_equivalent += 1;
+            }
             Err(e) => {
-                //println!("Result {}: Err\n{}", index, e);
-                return Err(format!("Error processing result {}: {}", index, e));
+                failed += 1;
+                if report {
+                    continue;
+                }
+                match e {
+                    FormatError::Parse(_) | FormatError::Enrich(_) => eprintln!("{}\n", e),
+                    _ => eprintln!("Error processing result {}: {}\n", path, e),
+                }
             }
         }
     }
 
-    Ok(())
+    if failed > 0 || not_equivalent > 0 {
+        let mut message = Vec::new();
+        if failed > 0 {
+            message.push(format!("{} file(s) could not be formatted.", failed));
+        }
+        if not_equivalent > 0 {
+            message.push(format!(
+                "{} file(s) left as they were, formatting would change their syntax tree.",
+                not_equivalent
+            ));
+        }
+        return Err(message.join("\n"));
+    }
+
+    if not_idempotent > 0 {
+        if !report {
+            println!(
+                "\n{} file(s) are not formatted idempotently.",
+                not_idempotent
+            );
+        }
+        return Ok(EXIT_NOT_IDEMPOTENT);
+    }
+
+    if unformatted > 0 && args.check {
+        if !report {
+            println!("\n{} file(s) would be reformatted.", unformatted);
+        }
+        return Ok(EXIT_UNFORMATTED);
+    }
+
+    Ok(0)
+}
+
+// one `--report json` record; `original` is the file content for `Ok` results
+fn report_record(
+    path: &str,
+    result: &Result<String, FormatError>,
+    original: Option<&str>,
+    duration: Duration,
+) -> Value {
+    let (status, error) = match result {
+        Ok(value) if original == Some(value.as_str()) => ("unchanged", Value::Null),
+        Ok(_) => ("changed", Value::Null),
+        Err(e) => {
+            let status = match e {
+                FormatError::Parse(_) => "parse_error",
+                _ => "internal_error",
+            };
+            let message = strip_colors(&e.to_string());
+            let message = message.lines().next().unwrap_or_default();
+            let location = e.location();
+            let error = json!({
+                "kind": e.kind(),
+                "message": message.strip_prefix("error: ").unwrap_or(message),
+                "line": location.map(|(line, _)| line),
+                "column": location.map(|(_, column)| column),
+            });
+            (status, error)
+        }
+    };
+    let (lines_removed, lines_added) = match (result, original) {
+        (Ok(value), Some(original)) => changed_line_counts(original, value),
+        _ => (0, 0),
+    };
+
+    json!({
+        "path": path,
+        "status": status,
+        "error": error,
+        "duration_ms": duration.as_secs_f64() * 1000.0,
+        "lines_added": lines_added,
+        "lines_removed": lines_removed,
+    })
+}
+
+// files and lines changed according to git or a patch, restricted to `args.paths`
+fn changed_files(args: &Args) -> Result<Option<ChangedFiles>, String> {
+    let mut changed = if let Some(ref patch) = args.patch {
+        let mut content = String::new();
+        if patch == "-" {
+            io::stdin()
+                .read_to_string(&mut content)
+                .map_err(|e| format!("Failed to read patch from stdin: {}", e))?;
+        } else {
+            content = fs::read_to_string(patch)
+                .map_err(|e| format!("Failed to read patch {}: {}", patch, e))?;
+        }
+        parse_patch(&content)
+    } else if args.changed_since.is_some() || args.staged {
+        changed_ranges(args.changed_since.as_deref(), args.staged)?
+    } else {
+        return Ok(None);
+    };
+
+    if !args.paths.is_empty() {
+        let selected: HashSet<_> = collect_source_files(&args.paths)?
+            .iter()
+            .filter_map(|path| std::path::absolute(path).ok())
+            .collect();
+        changed.retain(|(path, _)| {
+            std::path::absolute(path).is_ok_and(|path| selected.contains(&path))
+        });
+    }
+
+    Ok(Some(changed))
+}
+
+fn run_stdin(args: Args) -> Result<i32, String> {
+    let name = args.stdin_filepath.unwrap_or_else(|| "<stdin>".to_string());
+    let source_code = read_stdin(&name)?;
+    let config = config_for(args.config.as_deref(), &name)?;
+    let formatted = match args.range {
+        Some(range) => afmt::format_source_ranges(&source_code, &[range], &config),
+        None => afmt::format_source(&source_code, &config),
+    }
+    .map_err(|e| e.with_path(&name).to_string())?;
+
+    print!("{}", formatted);
+    Ok(0)
+}
+
+// prints the `Doc` tree of a single file, or of stdin
+fn run_dump_doc(args: Args) -> Result<i32, String> {
+    let (name, source_code) = if args.stdin {
+        let name = args.stdin_filepath.unwrap_or_else(|| "<stdin>".to_string());
+        let source_code = read_stdin(&name)?;
+        (name, source_code)
+    } else {
+        let Ok([path]) = <[String; 1]>::try_from(collect_source_files(&args.paths)?) else {
+            return Err("--dump-doc needs exactly one file".to_string());
+        };
+        let source_code = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read file {}: {}", path, e))?;
+        (path, source_code)
+    };
+
+    let config = config_for(args.config.as_deref(), &name)?;
+    let dump = Formatter::dump_doc(&source_code, &config, args.annotate_choices)
+        .map_err(|e| e.with_path(&name).to_string())?;
+
+    print!("{}", dump);
+    Ok(0)
+}
+
+fn run_inspect(inspect: Inspect) -> Result<i32, String> {
+    let source_code = fs::read_to_string(&inspect.file)
+        .map_err(|e| format!("Failed to read file {}: {}", inspect.file, e))?;
+
+    if inspect.ast {
+        print!("{}", Formatter::inspect_ast(&source_code));
+    }
+    if inspect.comments {
+        let comments = Formatter::inspect_comments(&source_code)
+            .map_err(|e| e.with_path(&inspect.file).to_string())?;
+        print!("{}", comments);
+    }
+    Ok(0)
+}
+
+fn read_stdin(name: &str) -> Result<String, String> {
+    let mut source_code = String::new();
+    io::stdin()
+        .read_to_string(&mut source_code)
+        .map_err(|e| format!("Failed to read {} from stdin: {}", name, e))?;
+    Ok(source_code)
+}
+
+// without --config, the config is looked up from `file`; for `<stdin>` that is
+// the current directory
+fn config_for(config_path: Option<&str>, file: &str) -> Result<Config, String> {
+    match config_path {
+        Some(path) => Config::from_file(path),
+        None => Config::discover(Path::new(file)),
+    }
+    .map_err(|e| e.to_string())
 }
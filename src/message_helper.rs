@@ -20,3 +20,41 @@ pub fn yellow(text: &str) -> &str {
     //format!("<strong style=\"color: yellow;\">{}</strong>", text) // HTML bold yellow
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+pub fn green(text: &str) -> String {
+    format!("\x1b[32m{}\x1b[0m", text) // ANSI green
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn green(text: &str) -> &str {
+    text
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn cyan(text: &str) -> String {
+    format!("\x1b[36m{}\x1b[0m", text) // ANSI cyan
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn cyan(text: &str) -> &str {
+    text
+}
+
+// `text` without the ANSI colors added by the functions above
+pub fn strip_colors(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // skip up to and including the final `m` of `\x1b[..m`
+            for c in chars.by_ref() {
+                if c == 'm' {
+                    break;
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
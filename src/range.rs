@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use tree_sitter::{Node, Tree};
+
+// the named children of these nodes are the statements and members a range
+// is widened to
+const BODY_KINDS: [&str; 7] = [
+    "parser_output",
+    "class_body",
+    "interface_body",
+    "enum_body",
+    "block",
+    "constructor_body",
+    "switch_block",
+];
+
+/// Part of a source file to format.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SourceRange {
+    /// byte offsets, end exclusive
+    Bytes(Range<usize>),
+    /// 1-based line numbers, both inclusive
+    Lines(usize, usize),
+}
+
+impl SourceRange {
+    /// Byte offsets of the range within `source_code`, clamped to its length.
+    pub fn to_bytes(&self, source_code: &str) -> Range<usize> {
+        match self {
+            SourceRange::Bytes(range) => {
+                let end = range.end.min(source_code.len());
+                range.start.min(end)..end
+            }
+            SourceRange::Lines(first, last) => {
+                let line_start = |line: usize| {
+                    source_code
+                        .match_indices('\n')
+                        .nth(line.saturating_sub(2))
+                        .map_or(source_code.len(), |(i, _)| i + 1)
+                };
+                let start = if *first <= 1 { 0 } else { line_start(*first) };
+                let end = line_start(last + 1).max(start);
+                start..end
+            }
+        }
+    }
+
+    // same range once every `\r\n` of `source_code` has become `\n`
+    pub(crate) fn without_carriage_returns(&self, source_code: &str) -> SourceRange {
+        match self {
+            SourceRange::Bytes(range) => {
+                // bytes, as an offset needs not be on a char boundary
+                let shift = |offset: usize| {
+                    let end = offset.min(source_code.len());
+                    let crlf = source_code.as_bytes()[..end]
+                        .windows(2)
+                        .filter(|pair| pair == b"\r\n")
+                        .count();
+                    offset - crlf
+                };
+                SourceRange::Bytes(shift(range.start)..shift(range.end))
+            }
+            SourceRange::Lines(first, last) => SourceRange::Lines(*first, *last),
+        }
+    }
+}
+
+fn is_unit(node: &Node) -> bool {
+    node.is_named()
+        && !node.is_extra()
+        && node
+            .parent()
+            .is_some_and(|parent| BODY_KINDS.contains(&parent.kind()))
+}
+
+fn overlaps(node: &Node, range: &Range<usize>) -> bool {
+    // an empty range still selects the node it points into
+    node.start_byte() < range.end.max(range.start + 1) && range.start < node.end_byte()
+}
+
+/// The smallest statements or members covering `range`. Nothing is returned
+/// when the range only touches whitespace or comments between them, and the
+/// root node when it is not inside any of them.
+pub(crate) fn covering_nodes<'t>(tree: &'t Tree, range: &Range<usize>) -> Vec<Node<'t>> {
+    let root = tree.root_node();
+    let deepest = root
+        .descendant_for_byte_range(range.start, range.end)
+        .unwrap_or(root);
+
+    let mut node = deepest;
+    loop {
+        if is_unit(&node) {
+            return vec![node];
+        }
+        if BODY_KINDS.contains(&node.kind()) {
+            let mut cursor = node.walk();
+            let units: Vec<_> = node
+                .named_children(&mut cursor)
+                .filter(|n| !n.is_extra() && overlaps(n, range))
+                .collect();
+            if !units.is_empty() {
+                return units;
+            }
+
+            // the braces of the body belong to the unit around it
+            let mut cursor = node.walk();
+            let mut tokens = node.children(&mut cursor).filter(|n| !n.is_named());
+            if !tokens.any(|n| overlaps(&n, range)) {
+                return Vec::new();
+            }
+        }
+        match node.parent() {
+            Some(parent) => node = parent,
+            None => return vec![root],
+        }
+    }
+}
+
+/// Ids of `nodes` and of every statement or member around them, in case a
+/// node has no output of its own and has to be widened.
+pub(crate) fn unit_ids(nodes: &[Node]) -> Vec<usize> {
+    let mut ids = Vec::new();
+    for node in nodes {
+        let mut current = Some(*node);
+        while let Some(n) = current {
+            if is_unit(&n) {
+                ids.push(n.id());
+            }
+            current = n.parent();
+        }
+    }
+    ids
+}
+
+/// Replaces `nodes` in `source_code` with their formatted text, found in
+/// `output` through `marks`, and keeps every other byte as it was.
+pub(crate) fn splice(
+    source_code: &str,
+    nodes: &[Node],
+    output: &str,
+    marks: &HashMap<usize, Range<usize>>,
+) -> String {
+    let mut targets: Vec<(Node, Range<usize>)> = Vec::new();
+
+    for node in nodes {
+        // widen to the closest enclosing statement or member that was printed
+        let mut current = Some(*node);
+        let target = loop {
+            match current {
+                Some(n) if n.parent().is_none() => return output.to_string(),
+                Some(n) => match marks.get(&n.id()) {
+                    Some(mark) if is_unit(&n) => break (n, mark.clone()),
+                    _ => current = n.parent(),
+                },
+                None => return output.to_string(),
+            }
+        };
+        targets.push(target);
+    }
+
+    targets.sort_by_key(|(n, _)| (n.start_byte(), std::cmp::Reverse(n.end_byte())));
+
+    let mut result = String::with_capacity(source_code.len());
+    let mut copied_until = 0;
+
+    for (node, mark) in targets {
+        // nested in, or the same as, a node replaced already
+        if node.start_byte() < copied_until {
+            continue;
+        }
+        result.push_str(&source_code[copied_until..node.start_byte()]);
+        result.push_str(&reindent(source_code, &node, output, &mark));
+        copied_until = node.end_byte();
+    }
+
+    result.push_str(&source_code[copied_until..]);
+    result
+}
+
+// formatted text of `node`, with the indentation of the formatted output
+// swapped for the indentation the node has in the source
+fn reindent(source_code: &str, node: &Node, output: &str, mark: &Range<usize>) -> String {
+    let formatted = &output[mark.clone()];
+
+    let source_indent = leading_whitespace(line_at(source_code, node.start_byte()));
+    let output_indent = leading_whitespace(line_at(output, mark.start));
+
+    let mut lines = formatted.split('\n');
+    let mut result = lines.next().unwrap_or("").to_string();
+    for line in lines {
+        result.push('\n');
+        match line.strip_prefix(output_indent) {
+            Some("") => {}
+            Some(rest) => {
+                result.push_str(source_indent);
+                result.push_str(rest);
+            }
+            None => result.push_str(line),
+        }
+    }
+    result
+}
+
+fn line_at(text: &str, offset: usize) -> &str {
+    let start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = text[offset..].find('\n').map_or(text.len(), |i| offset + i);
+    &text[start..end]
+}
+
+fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
@@ -4,99 +4,124 @@ use crate::{
     data_model::*,
     doc::{Doc, DocRef},
     doc_builder::DocBuilder,
-    enum_def::{Comparison, SetValue, SoqlLiteral, ValueComparedWith}, message_helper::{red, yellow},
+    enum_def::{Comparison, SetValue, SoqlLiteral, ValueComparedWith},
+    error::EnrichError,
 };
 #[allow(unused_imports)]
 use log::debug;
-use std::{cell::Cell, collections::HashMap};
 use tree_sitter::{Node, Tree, TreeCursor};
 
-thread_local! {
-    static THREAD_SOURCE_CODE: Cell<Option<&'static str>>
-        = const{ Cell::new(None) };
-}
-
-pub fn set_thread_source_code(source_code: String) {
-    // TODO: use OnceCell to not leak?
-    let leaked_code: &'static str = Box::leak(source_code.into_boxed_str());
-    THREAD_SOURCE_CODE.with(|sc| {
-        if sc.get().is_some() {
-            panic!("Source code is already set for this thread");
-        }
-        sc.set(Some(leaked_code));
-    });
-}
+// comment directives that keep the original source of the nodes they cover
+const AFMT_IGNORE: &str = "afmt-ignore";
+const AFMT_OFF: &str = "afmt-off";
+const AFMT_ON: &str = "afmt-on";
 
-pub fn get_source_code() -> &'static str {
-    THREAD_SOURCE_CODE.with(|sc| sc.get().expect("Source code not set for this thread"))
-}
+// every node holding comments, in source order, with its pre, post and
+// dangling comments and the metadata flags set on each
+pub fn dump_comment_map(tree: &Tree, comment_map: &CommentMap, source_code: &str) -> String {
+    let mut result = String::new();
 
-thread_local! {
-    static THREAD_COMMENT_MAP: Cell<Option<&'static CommentMap>> = const{ Cell::new(None) };
-}
-
-pub fn set_thread_comment_map(comment_map: CommentMap) {
-    // TODO: use OnceCell to not leak?
-    let leaked_map: &'static CommentMap = Box::leak(Box::new(comment_map));
-
-    THREAD_COMMENT_MAP.with(|cm| {
-        if cm.get().is_some() {
-            panic!("CommentMap is already set for this thread");
+    for node in nodes_in_order(tree) {
+        let Some(bucket) = comment_map.get(&node.id()) else {
+            continue;
+        };
+        let groups = [
+            ("pre", &bucket.pre_comments),
+            ("post", &bucket.post_comments),
+            ("dangling", &bucket.dangling_comments),
+        ];
+        if groups.iter().all(|(_, comments)| comments.is_empty()) {
+            continue;
         }
-        cm.set(Some(leaked_map));
-    });
-}
 
-pub fn get_comment_bucket(node_id: &usize) -> &CommentBucket {
-    get_comment_map()
-        .get(node_id)
-        .unwrap_or_else(|| panic!("## comment_map missing bucket for node: {}", node_id))
-}
+        result.push_str(&format!(
+            "{} {} {:?}\n",
+            node.kind(),
+            point_range(&node),
+            node.value(source_code).lines().next().unwrap_or("")
+        ));
+        for (name, comments) in groups {
+            for comment in comments {
+                let point = comment.range.start_point;
+                result.push_str(&format!(
+                    "  {} {}:{} {:?}",
+                    name,
+                    point.row + 1,
+                    point.column + 1,
+                    comment.value
+                ));
+                for flag in comment.metadata.flags() {
+                    result.push(' ');
+                    result.push_str(flag);
+                }
+                result.push('\n');
+            }
+        }
+    }
 
-pub fn get_comment_map() -> &'static CommentMap {
-    THREAD_COMMENT_MAP.with(|cm| cm.get().expect("## CommentMap not set for this thread"))
+    result
 }
 
-// Used for debugging purpose only
-pub fn print_comment_map(tree: &Tree) {
-    let comment_map = get_comment_map();
-    let node_map = build_id_node_map(tree);
-
-    let filtered_map: HashMap<usize, &CommentBucket> = comment_map
-        .iter()
-        .filter(|(_, bucket)| {
-            !bucket.pre_comments.is_empty()
-                || !bucket.post_comments.is_empty()
-                || !bucket.dangling_comments.is_empty()
-        })
-        .map(|(k, v)| (*k, v))
-        .collect();
+// one node per line with its field name, kind and 1-based `line:column`
+// range; named leaves also show their text
+pub fn dump_ast(tree: &Tree, source_code: &str) -> String {
+    let mut result = String::new();
+    let mut cursor = tree.walk();
+    let mut depth = 0;
 
-    for (node_id, bucket) in &filtered_map {
-        if let Some(node) = node_map.get(node_id) {
-            eprintln!(
-                "{}, {} ({}) : CommentBucket {{",
-                node_id,
-                yellow(node.kind()),
-                yellow(&node.value().chars().take(8).collect::<String>())
-            );
+    loop {
+        let node = cursor.node();
+        result.push_str(&"  ".repeat(depth));
+        if let Some(field) = cursor.field_name() {
+            result.push_str(field);
+            result.push_str(": ");
+        }
+        if node.is_missing() {
+            result.push_str("MISSING ");
+        }
+        if node.is_named() {
+            result.push_str(node.kind());
         } else {
-            eprintln!("{} (Unknown Node) : CommentBucket {{", node_id);
+            result.push_str(&format!("{:?}", node.kind()));
+        }
+        result.push(' ');
+        result.push_str(&point_range(&node));
+        if node.is_named() && node.child_count() == 0 {
+            let text = source_code.get(node.byte_range()).unwrap_or("");
+            result.push_str(&format!(" {:?}", text));
+        }
+        result.push('\n');
+
+        if cursor.goto_first_child() {
+            depth += 1;
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                return result;
+            }
+            depth -= 1;
         }
-        eprintln!("pre_comments: {:#?},", bucket.pre_comments);
-        eprintln!("post_comments: {:#?},", bucket.post_comments);
-        eprintln!("dangling_comments: {:#?},", bucket.dangling_comments);
-        eprintln!("--------------------");
     }
 }
 
-fn build_id_node_map(ast_tree: &Tree) -> HashMap<usize, Node> {
+fn point_range(node: &Node) -> String {
+    let (start, end) = (node.start_position(), node.end_position());
+    format!(
+        "[{}:{}-{}:{}]",
+        start.row + 1,
+        start.column + 1,
+        end.row + 1,
+        end.column + 1
+    )
+}
+
+fn nodes_in_order(ast_tree: &Tree) -> Vec<Node<'_>> {
     let mut cursor = ast_tree.walk();
-    let mut node_map = HashMap::new();
+    let mut nodes = Vec::new();
 
     loop {
-        let node = cursor.node();
-        node_map.insert(node.id(), node);
+        nodes.push(cursor.node());
 
         if cursor.goto_first_child() {
             continue;
@@ -104,14 +129,15 @@ fn build_id_node_map(ast_tree: &Tree) -> HashMap<usize, Node> {
 
         while !cursor.goto_next_sibling() {
             if !cursor.goto_parent() {
-                return node_map;
+                return nodes;
             }
         }
     }
 }
 
-pub fn assert_no_missing_comments() {
-    let missing_comments: Vec<&'static Comment> = get_comment_map()
+// comments never printed while building the doc
+pub fn lost_comments(comment_map: &CommentMap) -> Vec<&Comment> {
+    let mut lost: Vec<&Comment> = comment_map
         .values()
         .flat_map(|bucket| {
             bucket
@@ -122,20 +148,28 @@ pub fn assert_no_missing_comments() {
         })
         .filter(|comment| !comment.is_printed())
         .collect();
+    lost.sort_by_key(|comment| comment.range.start_byte);
+    lost
+}
 
-    if !missing_comments.is_empty() {
-        for comment in missing_comments {
-            eprintln!("Erased comment: {}", red(&comment.value));
-        }
-        panic!("## There are erased comment node(s)");
-    }
+// the comments of `ast_tree`, keyed by the id of the node they belong to
+pub fn build_comment_map(ast_tree: &Tree, source_code: &str) -> Result<CommentMap, EnrichError> {
+    let mut cursor = ast_tree.walk();
+    let mut comment_map = CommentMap::new();
+    collect_comments(&mut cursor, &mut comment_map, source_code)
+        .map_err(|e| e.with_snippet(source_code))?;
+    Ok(comment_map)
 }
 
-pub fn collect_comments(cursor: &mut TreeCursor, comment_map: &mut CommentMap) {
+pub fn collect_comments(
+    cursor: &mut TreeCursor,
+    comment_map: &mut CommentMap,
+    source_code: &str,
+) -> Result<(), EnrichError> {
     let node = cursor.node();
 
     if !node.is_named() || node.is_extra() {
-        return;
+        return Ok(());
     }
 
     let current_id = node.id();
@@ -145,13 +179,16 @@ pub fn collect_comments(cursor: &mut TreeCursor, comment_map: &mut CommentMap) {
 
     // If this node has no children, we simply return
     if !cursor.goto_first_child() {
-        return;
+        return Ok(());
     }
 
     // We'll track comments that appear before the next code node in this vector
     let mut pending_pre_comments = Vec::new();
     // Track the last visited code node
     let mut last_code_node_info: Option<(usize, usize)> = None;
+    // `afmt-ignore` applies to the next code node, `afmt-off` until `afmt-on`
+    let mut ignore_next = false;
+    let mut formatting_off = false;
 
     loop {
         let child = cursor.node();
@@ -159,7 +196,14 @@ pub fn collect_comments(cursor: &mut TreeCursor, comment_map: &mut CommentMap) {
         if child.is_named() {
             if child.is_extra() {
                 // It's a comment node
-                let comment = Comment::from_node(child);
+                let comment = Comment::from_node(child, source_code)?;
+                if comment.is_directive(AFMT_IGNORE) {
+                    ignore_next = true;
+                } else if comment.is_directive(AFMT_OFF) {
+                    formatting_off = true;
+                } else if comment.is_directive(AFMT_ON) {
+                    formatting_off = false;
+                }
 
                 if let Some((last_id, last_row)) = last_code_node_info {
                     if child.end_position().row == last_row {
@@ -188,7 +232,12 @@ pub fn collect_comments(cursor: &mut TreeCursor, comment_map: &mut CommentMap) {
                 }
 
                 // Recurse down into the child code node
-                collect_comments(cursor, comment_map);
+                collect_comments(cursor, comment_map, source_code)?;
+
+                if ignore_next || formatting_off {
+                    ignore_node(&child, comment_map);
+                    ignore_next = false;
+                }
 
                 // After returning, we know child is fully processed
                 last_code_node_info = Some((child_id, child.end_position().row));
@@ -219,6 +268,14 @@ pub fn collect_comments(cursor: &mut TreeCursor, comment_map: &mut CommentMap) {
 
     // Step back up to the parent node
     cursor.goto_parent();
+    Ok(())
+}
+
+// `node` is printed as in the source, comments inside included, see `build_unit`
+pub fn ignore_node(node: &Node, comment_map: &mut CommentMap) {
+    if let Some(bucket) = comment_map.get_mut(&node.id()) {
+        bucket.ignored_source = Some(node.byte_range());
+    }
 }
 
 pub fn build_with_comments<'a, F>(
@@ -229,13 +286,70 @@ pub fn build_with_comments<'a, F>(
 ) where
     F: FnOnce(&'a DocBuilder<'a>, &mut Vec<DocRef<'a>>),
 {
-    let bucket = get_comment_bucket(id);
+    build_unit(b, id, result, handle_members, |b, bucket, result| {
+        result.push(b.concat(handle_dangling_comments(b, bucket)));
+    });
+}
+
+// `build_with_comments` for `{ ... }` bodies, whose dangling comments go between the brackets
+pub fn build_body_with_comments<'a, F>(
+    b: &'a DocBuilder<'a>,
+    id: &usize,
+    result: &mut Vec<DocRef<'a>>,
+    handle_members: F,
+) where
+    F: FnOnce(&'a DocBuilder<'a>, &mut Vec<DocRef<'a>>),
+{
+    build_unit(
+        b,
+        id,
+        result,
+        handle_members,
+        handle_dangling_comments_in_bracket_surround,
+    );
+}
+
+fn build_unit<'a, F, D>(
+    b: &'a DocBuilder<'a>,
+    id: &usize,
+    result: &mut Vec<DocRef<'a>>,
+    handle_members: F,
+    handle_dangling: D,
+) where
+    F: FnOnce(&'a DocBuilder<'a>, &mut Vec<DocRef<'a>>),
+    D: FnOnce(&'a DocBuilder<'a>, &CommentBucket, &mut Vec<DocRef<'a>>),
+{
+    let bucket = b.comment_bucket(id);
     handle_pre_comments(b, bucket, result);
 
-    if bucket.dangling_comments.is_empty() {
-        handle_members(b, result);
+    if let Some(ref range) = bucket.ignored_source {
+        let source = &b.source_code()[range.clone()];
+        result.push(b.mark(*id, b.verbatim(source)));
+
+        // only now, so that a node whose source never gets printed still
+        // shows up in `lost_comments`
+        b.comment_map()
+            .values()
+            .flat_map(|bucket| {
+                [
+                    &bucket.pre_comments,
+                    &bucket.post_comments,
+                    &bucket.dangling_comments,
+                ]
+            })
+            .flatten()
+            .filter(|c| range.start <= c.range.start_byte && c.range.end_byte <= range.end)
+            .for_each(Comment::mark_as_printed);
+    } else if bucket.dangling_comments.is_empty() {
+        if b.is_marked(id) {
+            let mut docs = Vec::new();
+            handle_members(b, &mut docs);
+            result.push(b.mark(*id, b.concat(docs)));
+        } else {
+            handle_members(b, result);
+        }
     } else {
-        result.push(b.concat(handle_dangling_comments(b, bucket)));
+        handle_dangling(b, bucket, result);
         return;
     }
 
@@ -365,26 +479,26 @@ pub fn handle_post_comments<'a>(
     result.push(b.concat(docs));
 }
 
-pub fn enrich(ast_tree: &Tree) -> Root {
+pub fn enrich(ast_tree: &Tree, source: &str) -> Result<Root, EnrichError> {
     let root_node = ast_tree.root_node();
-    Root::new(root_node)
+    Root::new(root_node, source).map_err(|e| e.with_snippet(source))
     // TODO: check enum size
     //eprintln!("Root={:#?}", std::mem::size_of::<Root>());
     //eprintln!("Class={:#?}", std::mem::size_of::<FieldDeclaration>());
 }
 
-pub fn assert_check(node: Node, expected_kind: &str) {
-    assert!(
-        node.kind() == expected_kind,
-        "## Expected node kind '{}', found '{}'.\n## Source_code: {}",
-        yellow(expected_kind),
-        red(node.kind()),
-        node.value()
-    );
+pub fn assert_check(node: Node, expected_kind: &str) -> Result<(), EnrichError> {
+    if node.kind() != expected_kind {
+        return Err(EnrichError::new(
+            &node,
+            format!("expected node kind `{}`", expected_kind),
+        ));
+    }
+    Ok(())
 }
 
-pub fn get_precedence(op: &str) -> u8 {
-    match op {
+pub fn get_precedence(op_node: &Node) -> Result<u8, EnrichError> {
+    let precedence = match op_node.kind() {
         "=" | "+=" | "-=" | "*=" | "/=" | "%=" | "&=" | "|=" | "^=" | "<<=" | ">>=" | ">>>=" => 1, // Assignment
         "?" | ":" => 2,                               // Ternary
         "||" => 3,                                    // Logical OR
@@ -399,8 +513,9 @@ pub fn get_precedence(op: &str) -> u8 {
         "+" | "-" => 12,                              // Additive
         "*" | "/" | "%" => 13,                        // Multiplicative
         "!" | "~" | "++" | "--" => 14,                // Unary operators
-        _ => panic!("## Not supported operator: {}", op),
-    }
+        _ => return Err(EnrichError::new(op_node, "operator is not supported")),
+    };
+    Ok(precedence)
 }
 
 pub fn is_binary_exp(node: &Node) -> bool {
@@ -413,28 +528,28 @@ pub fn is_query_expression(node: &Node) -> bool {
 
 // TODO: AST use a comparison concrete node so this can be moved into Comparison::new()
 // TODO: get rid of next_named()?
-pub fn get_comparsion(node: &Node) -> Comparison {
+pub fn get_comparsion(node: &Node, source: &str) -> Result<Comparison, EnrichError> {
     if let Some(operator_node) = node.try_c_by_k("value_comparison_operator") {
-        let next_node = operator_node.next_named();
+        let next_node = operator_node.next_named()?;
         let compared_with = match next_node.kind() {
             "bound_apex_expression" => {
-                ValueComparedWith::Bound(BoundApexExpression::new(next_node))
+                ValueComparedWith::Bound(BoundApexExpression::new(next_node, source)?)
             }
-            _ => ValueComparedWith::Literal(SoqlLiteral::new(next_node)),
+            _ => ValueComparedWith::Literal(SoqlLiteral::new(next_node, source)?),
         };
 
-        Comparison::Value(ValueComparison {
-            operator: operator_node.value(),
+        Ok(Comparison::Value(ValueComparison {
+            operator: operator_node.value(source),
             compared_with,
-        })
+        }))
     } else if let Some(operator_node) = node.try_c_by_k("set_comparison_operator") {
-        let next_node = operator_node.next_named();
-        Comparison::Set(SetComparison {
-            operator: operator_node.value(),
-            set_value: SetValue::new(next_node),
-        })
+        let next_node = operator_node.next_named()?;
+        Ok(Comparison::Set(SetComparison {
+            operator: operator_node.value(source),
+            set_value: SetValue::new(next_node, source)?,
+        }))
     } else {
-        unreachable!()
+        Err(EnrichError::new(node, "missing comparison operator"))
     }
 }
 
@@ -472,13 +587,8 @@ fn is_a_chaining_node(node: &Node) -> bool {
     .contains(&node.kind())
 }
 
-pub fn panic_unknown_node(node: Node, name: &str) -> ! {
-    panic!(
-        "## unknown node: {} in {}\n## Source_code: {}",
-        red(node.kind()),
-        name,
-        node.value()
-    );
+pub fn unknown_node(node: Node, name: &str) -> EnrichError {
+    EnrichError::new(&node, format!("unknown node in {}", name))
 }
 
 pub fn is_bracket_composite_node(node: &Node) -> bool {
@@ -1,11 +1,12 @@
 #[cfg(test)]
 mod tests {
+    use afmt::error::FormatError;
     use afmt::message_helper::{self, red};
     use afmt::{formatter::*, message_helper::yellow};
     use similar::{ChangeTag, TextDiff};
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io::Write;
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
     use std::process::Command;
 
     #[test]
@@ -26,6 +27,425 @@ mod tests {
         assert_eq!(failed, 0, "{} out of {} tests failed", failed, total);
     }
 
+    #[test]
+    fn parse_error() {
+        let source = "public class A {\n    Integer i = ;\n}\n";
+        match Formatter::format_one(source, Config::default()) {
+            Err(FormatError::Parse(e)) => {
+                assert_eq!(e.line, 2);
+                assert!(e.to_string().contains("Integer i = ;"));
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn collect_source_files_walks_globs_and_dedupes() {
+        use afmt::files::collect_source_files;
+
+        let root = TempDir::new("files");
+        for dir in ["classes", ".sfdx", "node_modules"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in [
+            "classes/A.cls",
+            "classes/notes.txt",
+            "B.trigger",
+            ".sfdx/C.cls",
+            "node_modules/D.cls",
+        ] {
+            fs::write(root.join(file), "").unwrap();
+        }
+        let path = |p: &str| root.join(p).to_string_lossy().to_string();
+
+        let files = collect_source_files(&[
+            path(""),
+            path("classes/../classes/A.cls"),
+            path("*.trigger"),
+        ])
+        .unwrap();
+        assert_eq!(files, vec![path("B.trigger"), path("classes/A.cls")]);
+
+        let e = collect_source_files(&[path(""), path("*.page")]).unwrap_err();
+        assert!(e.contains("No files match"));
+    }
+
+    #[test]
+    fn format_source_many_times_in_one_thread() {
+        let config = Config::default();
+        let first = afmt::format_source("class A{}", &config).expect("format failed");
+        let second = afmt::format_source(&first, &config).expect("format failed");
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn format_keeps_input_order() {
+        let files: Vec<String> = ["Ternary.in", "DmlClass.in", "NewObject.in", "assignment.in"]
+            .iter()
+            .map(|f| format!("tests/static/{}", f))
+            .collect();
+        let formatter = Formatter::new(Config::default(), files.clone()).with_jobs(2);
+        let paths: Vec<String> = formatter.format().into_iter().map(|(p, _)| p).collect();
+        assert_eq!(paths, files);
+    }
+
+    #[test]
+    fn config_discovery_stops_at_project_root() {
+        let root = TempDir::new("discovery");
+        let package = root.join("project/pkg/classes");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(root.join(".afmt.toml"), "indent_size = 8\n").unwrap();
+        fs::write(root.join("project/sfdx-project.json"), "{}").unwrap();
+
+        let file = package.join("A.cls");
+        assert_eq!(Config::find_config_file(&file), None);
+
+        fs::write(root.join("project/pkg/.afmt.toml"), "indent_size = 4\n").unwrap();
+        assert_eq!(
+            Config::find_config_file(&file),
+            Some(root.join("project/pkg/.afmt.toml"))
+        );
+        assert_eq!(Config::discover(&file).unwrap().indent_size(), 4);
+    }
+
+    #[test]
+    fn config_rejects_unknown_keys_and_bad_values() {
+        let e = Config::from_toml("max_width = 100\nmax_widht = 100\n").unwrap_err();
+        assert_eq!((e.line, e.column), (2, 1));
+        assert!(e.message.contains("did you mean `max_width`?"));
+
+        let e = Config::from_toml("indent_size = 0\n").unwrap_err();
+        assert_eq!((e.line, e.column), (1, 15));
+
+        assert!(Config::from_toml("max_width = 0").is_err());
+        assert_eq!(Config::from_toml("").unwrap().indent_size(), 2);
+    }
+
+    #[test]
+    fn indent_with_tabs() {
+        let config = Config::from_toml("indent_style = \"tabs\"\n").unwrap();
+        let source = "class A {\n  void m() {\n    Integer i = 1;\n  }\n}\n";
+        let formatted = afmt::format_source(source, &config).expect("format failed");
+        assert_eq!(
+            formatted,
+            "class A {\n\tvoid m() {\n\t\tInteger i = 1;\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn keep_crlf_and_missing_final_newline() {
+        let config = Config::from_toml("insert_final_newline = false\n").unwrap();
+        let source = "class A {\r\n  Integer i = 1;\r\n}";
+        let formatted = afmt::format_source(source, &config).expect("format failed");
+        assert_eq!(formatted, "class A {\r\n  Integer i = 1;\r\n}");
+
+        let config = Config::from_toml("newline_style = \"lf\"\n").unwrap();
+        let formatted = afmt::format_source(source, &config).expect("format failed");
+        assert_eq!(formatted, "class A {\n  Integer i = 1;\n}\n");
+    }
+
+    #[test]
+    fn text_width_in_display_columns() {
+        // 15 wide chars take 30 columns but 45 bytes
+        let source = "class A {\n  String s = '日本語のテキストはここに書かれ';\n}\n";
+        let config = Config::new(50);
+        let formatted = afmt::format_source(source, &config).expect("format failed");
+        assert_eq!(formatted, source);
+    }
+
+    #[test]
+    fn visual_continuation_aligns_arguments() {
+        let mut config = Config::from_toml("continuation_style = \"visual\"\n").unwrap();
+        config.max_width = 40;
+        let source = "class A {\n  void m() {\n    foo(aaaaaaaaaaaaaaaaaaaa, bbbbbbbbbbbbbbbbbbbb);\n  }\n}\n";
+        let formatted = afmt::format_source(source, &config).expect("format failed");
+        assert_eq!(
+            formatted,
+            "class A {\n  void m() {\n    foo(aaaaaaaaaaaaaaaaaaaa,\n        bbbbbbbbbbbbbbbbbbbb);\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn format_only_given_lines() {
+        let config = Config::default();
+        let source = "class A{\n  void f() {\n    Integer  a=1;\n    Integer  b=2;\n  }\n}\n";
+        let formatted =
+            afmt::format_source_ranges(source, &[afmt::SourceRange::Lines(3, 3)], &config)
+                .expect("format failed");
+        assert_eq!(
+            formatted,
+            "class A{\n  void f() {\n    Integer a = 1;\n    Integer  b=2;\n  }\n}\n"
+        );
+
+        let source = "class A{\n  A() {\n    Integer  a=1;\n    Integer  b=2;\n  }\n}\n";
+        let formatted =
+            afmt::format_source_ranges(source, &[afmt::SourceRange::Lines(4, 4)], &config)
+                .expect("format failed");
+        assert_eq!(
+            formatted,
+            "class A{\n  A() {\n    Integer  a=1;\n    Integer b = 2;\n  }\n}\n"
+        );
+
+        // a blank line and a comment between statements select nothing
+        let source =
+            "class A{\n  void f() {\n    Integer  a=1;\n\n    // b\n    Integer  b=2;\n  }\n}\n";
+        for line in [4, 5] {
+            let formatted = afmt::format_source_ranges(
+                source,
+                &[afmt::SourceRange::Lines(line, line)],
+                &config,
+            )
+            .expect("format failed");
+            assert_eq!(formatted, source);
+        }
+
+        // an offset inside `é`
+        let source = "class A {\r\n  String s = 'é';\r\n}\r\n";
+        let inside = source.find('é').unwrap() + 1;
+        let formatted = afmt::format_source_ranges(
+            source,
+            &[afmt::SourceRange::Bytes(inside..inside)],
+            &config,
+        )
+        .expect("format failed");
+        assert_eq!(formatted, source);
+    }
+
+    #[test]
+    fn changed_lines_from_patch() {
+        let patch = "diff --git a/src/A.cls b/src/A.cls\n\
+                     --- a/src/A.cls\n\
+                     +++ b/src/A.cls\n\
+                     @@ -3,0 +4,2 @@ class A {\n\
+                     +  Integer a;\n\
+                     +  Integer b;\n\
+                     @@ -10 +12 @@\n\
+                     -x\n\
+                     +y\n\
+                     @@ -20,2 +21,0 @@\n\
+                     -a\n\
+                     -b\n\
+                     @@ -30,7 +30,8 @@\n\
+                     \x20 void f() {\n\
+                     \x20   a();\n\
+                     \x20   b();\n\
+                     +   c();\n\
+                     \x20 }\n\
+                     \x20 void g() {\n\
+                     -   d( );\n\
+                     +   d();\n\
+                     \x20 }\n\
+                     --- a/README.md\n\
+                     +++ b/README.md\n\
+                     @@ -1 +1 @@\n";
+        assert_eq!(
+            afmt::git::parse_patch(patch),
+            vec![(
+                "src/A.cls".to_string(),
+                vec![
+                    afmt::SourceRange::Lines(4, 5),
+                    afmt::SourceRange::Lines(12, 12),
+                    afmt::SourceRange::Lines(33, 33),
+                    afmt::SourceRange::Lines(36, 36)
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn verify_idempotent() {
+        let config = Config::default();
+        let formatted = Formatter::format_one("class A{Integer a=1;}", config.clone()).unwrap();
+        let mut parser = Formatter::new_parser();
+        assert!(Formatter::verify_idempotent_with_parser(&mut parser, &formatted, &config).is_ok());
+
+        let files = vec!["tests/to-do/twice_format_issue1.in".to_string()];
+        let formatter = Formatter::new(config, files).with_idempotency_check();
+        match formatter.format().pop() {
+            Some((_, Err(FormatError::NotIdempotent { first, second }))) => {
+                assert_ne!(first, second)
+            }
+            other => panic!("expected an idempotency failure, got {:?}", other),
+        }
+
+        let e = FormatError::NotIdempotent {
+            first: "class A {\n}\n".to_string(),
+            second: "class A {}\n".to_string(),
+        };
+        assert!(e.to_string().contains("-class A {\n-}\n+class A {}\n"));
+    }
+
+    #[test]
+    fn safety_check_finds_first_changed_node() {
+        let mut parser = Formatter::new_parser();
+        let source = "class A {\n  // note\n  Boolean b = TRUE;\n}\n";
+        let same = "class A { Boolean b = true; }";
+        assert!(Formatter::verify_equivalent_with_parser(&mut parser, source, same).is_ok());
+
+        let changed = "class A { Boolean b = true; Integer c; }";
+        match Formatter::verify_equivalent_with_parser(&mut parser, "class A {\n}\n", changed) {
+            Err(FormatError::NotEquivalent(e)) => {
+                // the closing `}` of the source where the output has a field
+                assert_eq!((e.kind, e.line, e.column), ("}", 2, 1));
+                assert!(e.found.unwrap().starts_with("`field_declaration`"));
+            }
+            other => panic!("expected a safety check failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn erased_comments_policy() {
+        let config = Config::from_toml("erased_comments = \"keep_source\"\n").unwrap();
+        assert_eq!(config.erased_comments, ErasedComments::KeepSource);
+        assert!(Config::from_toml("erased_comments = \"drop\"\n").is_err());
+
+        let e = afmt::error::LostCommentsError {
+            path: Some("A.cls".to_string()),
+            comments: vec![afmt::error::LostComment {
+                line: 12,
+                column: 5,
+                text: "// why".to_string(),
+            }],
+        };
+        assert!(e.to_string().contains("--> A.cls:12:5"));
+
+        // `ValueComparison` never prints the comments of its children
+        let source = fs::read_to_string("tests/lost_comments/value_comparison.in").unwrap();
+        match afmt::format_source(&source, &Config::default()) {
+            Err(FormatError::LostComments(e)) => {
+                assert_eq!(e.comments.len(), 1);
+                assert_eq!((e.comments[0].line, e.comments[0].column), (5, 65));
+                assert_eq!(e.comments[0].text, "/* c */");
+            }
+            other => panic!("expected a lost comment, got {:?}", other),
+        }
+
+        let expected = fs::read_to_string("tests/lost_comments/value_comparison.cls").unwrap();
+        assert_eq!(afmt::format_source(&source, &config).unwrap(), expected);
+    }
+
+    #[test]
+    fn dump_doc_annotates_choices() {
+        let source = "class A { void m() { foo(a, b); } }";
+        let dump = Formatter::dump_doc(source, &Config::default(), true).unwrap();
+        assert!(dump.contains("Text \"foo\""));
+        assert!(dump.contains("Choice [flat]"));
+
+        let dump = Formatter::dump_doc(source, &Config::default(), false).unwrap();
+        assert!(!dump.contains("[flat]"));
+
+        // every `group` shares its content between both branches, which must
+        // not double the dump per nesting level
+        let source = "class A { void m() { a(b(c(d(e(f(g(h(i(j(1)))))))))); } }";
+        let dump = Formatter::dump_doc(source, &Config::default(), true).unwrap();
+        assert!(dump.contains("Flat -> #"));
+        assert!(
+            dump.lines().count() < 1000,
+            "{} lines",
+            dump.lines().count()
+        );
+    }
+
+    #[test]
+    fn inspect_ast_and_comments() {
+        let source = "class A {\n  // note\n  Integer a;\n}\n";
+        let ast = Formatter::inspect_ast(source);
+        assert!(ast.contains("name: identifier [1:7-1:8] \"A\""));
+
+        let comments = Formatter::inspect_comments(source).unwrap();
+        assert!(comments.contains("pre 2:3 \"// note\""));
+    }
+
+    #[test]
+    fn report_parts() {
+        assert_eq!(
+            afmt::diff::changed_line_counts("a\nb\nc\n", "a\nB\nc\nd\n"),
+            (1, 2)
+        );
+        assert_eq!(message_helper::strip_colors(&red("A.cls")), "A.cls");
+
+        let e = Formatter::format_one("class A {\n  Integer i = ;\n}\n", Config::default())
+            .unwrap_err();
+        assert_eq!(e.kind(), "parse");
+        assert_eq!(e.location().map(|(line, _)| line), Some(2));
+
+        let files = vec!["tests/static/Ternary.in".to_string()];
+        let results = Formatter::new(Config::default(), files).format_timed();
+        assert_eq!(results.len(), 1);
+
+        // a bad `.afmt.toml` keeps its position
+        let root = TempDir::new("report");
+        fs::write(root.join(".afmt.toml"), "indent_size = 0\n").unwrap();
+        fs::write(root.join("A.cls"), "class A {}\n").unwrap();
+        let files = vec![root.join("A.cls").to_string_lossy().to_string()];
+        let formatter = Formatter::new(Config::default(), files).with_config_discovery();
+        match formatter.format().pop() {
+            Some((_, Err(e @ FormatError::Config(_)))) => assert_eq!(e.location(), Some((1, 15))),
+            other => panic!("expected a config error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn lsp_formats_and_reports_parse_errors() {
+        let messages = [
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+            r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"untitled:A","text":"class A { Integer a }"}}}"#,
+            r#"{"jsonrpc":"2.0","method":"textDocument/didChange","params":{"textDocument":{"uri":"untitled:A"},"contentChanges":[{"text":"class A {  }"}]}}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"textDocument/formatting","params":{"textDocument":{"uri":"untitled:A"}}}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"shutdown"}"#,
+            r#"{"jsonrpc":"2.0","method":"exit"}"#,
+        ];
+        let input: String = messages
+            .iter()
+            .map(|m| format!("Content-Length: {}\r\n\r\n{}", m.len(), m))
+            .collect();
+
+        let mut output = Vec::new();
+        afmt::lsp::run(input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains(r#""message":"missing `;`"#));
+        assert!(output.contains(r#""diagnostics":[]"#));
+        assert!(output.contains(r#""newText":"class A {\n}\n""#));
+    }
+
+    #[test]
+    fn lsp_keeps_serving_after_bad_messages() {
+        let root = TempDir::new("lsp");
+        fs::write(root.join(".afmt.toml"), "max_widht = 80\n").unwrap();
+        let uri = format!("file://{}/A.cls", root.to_string_lossy());
+
+        let messages = [
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#.to_string(),
+            r#"{"jsonrpc":"2.0","id":2,"method":"#.to_string(),
+            format!(
+                r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"{}","text":"class A {{}}"}}}}}}"#,
+                uri
+            ),
+            format!(
+                r#"{{"jsonrpc":"2.0","id":3,"method":"textDocument/formatting","params":{{"textDocument":{{"uri":"{}"}}}}}}"#,
+                uri
+            ),
+            r#"{"jsonrpc":"2.0","id":4,"method":"shutdown"}"#.to_string(),
+            r#"{"jsonrpc":"2.0","method":"exit"}"#.to_string(),
+        ];
+        let mut input: String = messages
+            .iter()
+            .map(|m| format!("Content-Length: {}\r\n\r\n{}", m.len(), m))
+            .collect();
+        // a message without a Content-Length header
+        input.insert_str(0, "Content-Type: application/json\r\n\r\n");
+
+        let mut output = Vec::new();
+        afmt::lsp::run(input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(output.matches(r#""code":-32700"#).count(), 2);
+        assert!(output.contains("unknown config key `max_widht`"));
+        assert!(!output.contains(r"\u001b"));
+        assert!(output.contains(r#""id":4"#));
+    }
+
     #[test]
     fn all() {
         let scenarios = [
@@ -186,7 +606,7 @@ mod tests {
         let vec = formatter.format();
         vec.into_iter()
             .next()
-            .and_then(|result| result.ok())
+            .and_then(|(_, result)| result.ok())
             .expect("format result failed.")
     }
 
@@ -313,4 +733,29 @@ mod tests {
         file.write_all(output.as_bytes())
             .expect("Failed to write Prettier output");
     }
+
+    // a directory under the system temp dir, removed with its content on drop
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("afmt_{}_{}", name, std::process::id()));
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl std::ops::Deref for TempDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
 }